@@ -0,0 +1,252 @@
+use std::fs::File;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+
+use rcalc::CalcError;
+use rcalc::Calculator;
+use rcalc::Format;
+use rcalc::Lang;
+use rcalc::Mode;
+use rcalc::Value;
+use rcalc::format;
+
+use cli::Input;
+use cli::Output;
+use json;
+
+
+/// Name shown in diagnostics for lines read from stdin.
+const STDIN_NAME: &str = "<stdin>";
+
+/// How results and errors are written.
+#[derive(Clone, Copy, Debug)]
+pub struct Report {
+    pub lang: Lang,
+    pub output: Output,
+}
+
+impl Report {
+    /// Writes the result of `input`. Definitions, which have no value, are written only as JSON.
+    fn result<O: Write>(
+        &self,
+        input: &str,
+        value: Option<&Value>,
+        format: Format,
+        mode: Mode,
+        out: &mut O,
+    ) -> io::Result<()> {
+        match (self.output, value) {
+            (Output::Text, Some(value)) => {
+                writeln!(out, "{}", format::format_result(value, format, mode))
+            }
+            (Output::Text, None) => Ok(()),
+            (Output::Json, value) => writeln!(out, "{}", json::result(input, value, format)),
+        }
+    }
+
+    /// Writes `e` prefixed with `location` to `err`, or as JSON with the failed `input` to `out`.
+    fn error<O: Write, E: Write>(
+        &self,
+        input: Option<&str>,
+        e: &CalcError,
+        location: &str,
+        out: &mut O,
+        err: &mut E,
+    ) -> io::Result<()> {
+        match self.output {
+            Output::Text => writeln!(err, "{}{}", location, e.localize(self.lang).trim_end()),
+            Output::Json => writeln!(out, "{}", json::error(input, e, self.lang)),
+        }
+    }
+}
+
+/// Evaluates `inputs` in order, sharing one calculator.
+/// Returns the exit code of the first failure, or 0 when every line succeeded.
+pub fn run_all(calc: &mut Calculator, inputs: &[Input], report: Report) -> i32 {
+    let stdout = io::stdout();
+    let stderr = io::stderr();
+    let mut out = stdout.lock();
+    let mut err = stderr.lock();
+
+    let mut status = 0;
+    for input in inputs {
+        let result = match *input {
+            Input::Expression(ref expr) => match eval_line(calc, expr, report, &mut out) {
+                Ok(()) => Ok(0),
+                Err(e) => {
+                    let _ = report.error(Some(expr), &e, "", &mut out, &mut err);
+                    Ok(e.exit_code())
+                }
+            },
+            Input::File(ref path) => File::open(path)
+                .and_then(|file| run(calc, path, BufReader::new(file), report, &mut out, &mut err))
+                .map_err(|e| (path.as_str(), e)),
+            Input::Stdin => {
+                let stdin = io::stdin();
+                run(calc, STDIN_NAME, stdin.lock(), report, &mut out, &mut err)
+                    .map_err(|e| (STDIN_NAME, e))
+            }
+        };
+
+        let code = match result {
+            Ok(code) => code,
+            Err((name, e)) => {
+                let e = CalcError::Io(e);
+                let _ = report.error(None, &e, &format!("{}: ", name), &mut out, &mut err);
+                e.exit_code()
+            }
+        };
+        if status == 0 {
+            status = code;
+        }
+    }
+    status
+}
+
+/// Evaluates `input` line by line, skipping blank lines and comments starting with `#`.
+/// Results are written to `out`, and errors to `err` with their line numbers or to `out` as JSON.
+/// Returns the exit code of the first line that failed, or 0.
+pub fn run<R, O, E>(
+    calc: &mut Calculator,
+    name: &str,
+    input: R,
+    report: Report,
+    out: &mut O,
+    err: &mut E,
+) -> io::Result<i32>
+where
+    R: BufRead,
+    O: Write,
+    E: Write,
+{
+    let mut status = 0;
+
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        match eval_line(calc, &line, report, out) {
+            Ok(()) => (),
+            Err(CalcError::Io(e)) => return Err(e),
+            Err(e) => {
+                if status == 0 {
+                    status = e.exit_code();
+                }
+                report.error(Some(&line), &e, &format!("{}:{}: ", name, i + 1), out, err)?;
+            }
+        }
+    }
+
+    Ok(status)
+}
+
+/// Evaluates one line and writes its result to `out`.
+fn eval_line<O: Write>(
+    calc: &mut Calculator,
+    line: &str,
+    report: Report,
+    out: &mut O,
+) -> rcalc::Result<()> {
+    let line = line.trim();
+
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(());
+    }
+
+    let result = calc.eval(line)?;
+    report.result(line, result.as_ref(), calc.last_format(), calc.mode(), out)?;
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    fn run_str(input: &str) -> (i32, String, String) {
+        run_str_in(input, Report { lang: Lang::Ja, output: Output::Text })
+    }
+
+    fn run_str_in(input: &str, report: Report) -> (i32, String, String) {
+        let mut calc = Calculator::new();
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+
+        let status = run(&mut calc, "test", Cursor::new(input), report, &mut out, &mut err)
+            .expect("Test returns Err().");
+
+        (status, String::from_utf8(out).unwrap(), String::from_utf8(err).unwrap())
+    }
+
+    //// run ////
+
+    #[test]
+    fn run_results_per_line() {
+        let (status, out, err) = run_str("1 + 2\nx = 4\nf(n) = n * x\nf(3)\n");
+
+        assert_eq!(status, 0);
+        assert_eq!(out, "3\n4\n12\n");
+        assert_eq!(err, "");
+    }
+
+    #[test]
+    fn run_skips_blank_and_comment() {
+        let (status, out, _) = run_str("\n# comment\n   \n  # indented\n7\n");
+
+        assert_eq!(status, 0);
+        assert_eq!(out, "7\n");
+    }
+
+    #[test]
+    fn run_reports_line_number() {
+        let (status, out, err) = run_str("1\n\n1 / 0\n2\nfoo\n");
+
+        assert_eq!(status, 5);
+        assert_eq!(out, "1\n2\n");
+        assert_eq!(err, "test:3: ゼロ除算エラー: 1 / 0\ntest:5: 未定義の変数です: foo\n");
+    }
+
+    #[test]
+    fn run_reports_in_lang() {
+        let report = Report { lang: Lang::En, output: Output::Text };
+        let (_, _, err) = run_str_in("1 / 0\nfoo\n", report);
+
+        assert_eq!(err, "test:1: Division by zero: 1 / 0\ntest:2: Undefined variable: foo\n");
+    }
+
+    #[test]
+    fn run_conversion() {
+        let (_, out, _) = run_str("255 to hex\n255\n");
+
+        assert_eq!(out, "0xff\n255\n");
+    }
+
+    #[test]
+    fn run_json() {
+        let report = Report { lang: Lang::En, output: Output::Json };
+        let (status, out, err) = run_str_in("7 * 6\n# comment\nf(x) = x\n1 / 0\n", report);
+
+        assert_eq!(status, 5);
+        assert_eq!(
+            out.lines().collect::<Vec<_>>(),
+            vec![
+                r#"{"input":"7 * 6","value":"42","type":"integer"}"#,
+                r#"{"input":"f(x) = x","value":null,"type":"definition"}"#,
+                concat!(
+                    r#"{"input":"1 / 0","error":{"kind":"division_by_zero","#,
+                    r#""message":"Division by zero: 1 / 0","span":null}}"#,
+                ),
+            ]
+        );
+        assert_eq!(err, "");
+    }
+
+    #[test]
+    fn run_first_failure_status() {
+        let (status, _, _) = run_str("1 +\n1 / 0\n");
+
+        assert_eq!(status, 4);
+    }
+}
@@ -0,0 +1,521 @@
+use std::sync::Arc;
+
+use error::Result;
+use execute;
+use execute::Mode;
+use execute::environment::Environment;
+use execute::environment::OperatorFunction;
+use execute::error::ExecuteResult;
+use execute::value::Value;
+use expression;
+use expression::Statement;
+use expression::operator::Notation;
+use format::Format;
+
+
+/// Name of the variable holding the previous result.
+pub const LAST_RESULT_NAME: &str = "ans";
+
+/// Line evaluated by a `Calculator` and its result.
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    pub input: String,
+    pub result: Value,
+    /// Format requested by a conversion such as `to hex` at the end of the line.
+    pub conversion: Option<Format>,
+}
+
+/// Evaluates lines one by one, keeping settings, variables, functions and history.
+///
+/// ```
+/// use rcalc::Calculator;
+///
+/// let mut calc = Calculator::new();
+/// calc.eval("x = 6").unwrap();
+/// calc.eval("f(n) = n * x").unwrap();
+///
+/// let result = calc.eval("f(7)").unwrap().unwrap();
+/// assert_eq!(result.to_string(), "42");
+/// ```
+#[derive(Debug, Default)]
+pub struct Calculator {
+    mode: Mode,
+    format: Format,
+    env: Environment,
+    history: Vec<Entry>,
+}
+
+impl Calculator {
+    pub fn new() -> Calculator {
+        Calculator::default()
+    }
+
+    /// Numeric backend used by `eval`. Defaults to `Mode::Int64`.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+    }
+
+    /// Output format of results. Defaults to `Format::Fraction`.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn set_format(&mut self, format: Format) {
+        self.format = format;
+    }
+
+    /// Variables and user-defined functions of this calculator.
+    pub fn environment(&self) -> &Environment {
+        &self.env
+    }
+
+    /// Upper bound of nested calls of user-defined functions.
+    pub fn max_depth(&self) -> usize {
+        self.env.max_depth()
+    }
+
+    /// Values above `execute::environment::MAX_DEPTH` are lowered to it.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.env.set_max_depth(max_depth);
+    }
+
+    /// Declares an operator implemented by `function`, which receives the values of the operands.
+    /// The operator can be used in the following input, like one declared with `infix`.
+    /// Fails if the symbol is the name of a function or a variable.
+    pub fn define_operator<F>(&mut self, notation: Notation, function: F) -> ExecuteResult<()>
+    where
+        F: Fn(&[Value], Mode) -> ExecuteResult<Value> + Send + Sync + 'static,
+    {
+        self.env.define_operator(notation, OperatorFunction::Native(Arc::new(function)))
+    }
+
+    /// Evaluates an expression, an assignment, a function or operator definition,
+    /// or a conversion such as `255 to hex`. Returns `None` for a definition, which has no value.
+    ///
+    /// The result is also stored in the variable `ans` and in the history.
+    pub fn eval(&mut self, line: &str) -> Result<Option<Value>> {
+        let stmt = expression::parse_statement_with(line, self.env.operators())?;
+        let conversion = match stmt {
+            Statement::Convert(_, format) => Some(format),
+            _ => None,
+        };
+        let result = match execute::execute_statement(stmt, self.mode, &mut self.env)? {
+            Some(result) => result,
+            None => return Ok(None),
+        };
+
+        self.env.set(LAST_RESULT_NAME, result.clone());
+        self.history.push(Entry {
+            input: line.to_string(),
+            result: result.clone(),
+            conversion,
+        });
+
+        Ok(Some(result))
+    }
+
+    /// Result of the latest successful evaluation.
+    pub fn last_result(&self) -> Option<&Value> {
+        self.history.last().map(|entry| &entry.result)
+    }
+
+    /// Evaluated lines in order, excluding failures and function definitions.
+    pub fn history(&self) -> &[Entry] {
+        &self.history
+    }
+
+    /// Format of the latest result: its conversion, or else the format of this calculator.
+    pub fn last_format(&self) -> Format {
+        self.history.last().and_then(|entry| entry.conversion).unwrap_or(self.format)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use error::CalcError;
+    use execute::IntType;
+    use execute::Overflow;
+    use execute::environment::MAX_DEPTH;
+    use execute::error::ExecuteError;
+    use expression::operator::Associativity;
+    use num_bigint::BigInt;
+    use num_rational::BigRational;
+
+    fn int(n: i64) -> Value {
+        Value::Integer(n.into())
+    }
+
+    #[test]
+    fn calculator_eval() {
+        let mut calc = Calculator::new();
+
+        let result = calc.eval("1 + 2");
+
+        assert_eq!(result.expect("Test returns Err()."), Some(int(3)));
+        assert_eq!(calc.last_result(), Some(&int(3)));
+    }
+
+    #[test]
+    fn calculator_keeps_history() {
+        let mut calc = Calculator::new();
+
+        calc.eval("1 + 2").expect("Test returns Err().");
+        calc.eval("3 * 4").expect("Test returns Err().");
+
+        assert_eq!(
+            calc.history(),
+            &[
+                Entry { input: "1 + 2".to_string(), result: int(3), conversion: None },
+                Entry { input: "3 * 4".to_string(), result: int(12), conversion: None },
+            ]
+        );
+        assert_eq!(calc.last_result(), Some(&int(12)));
+    }
+
+    #[test]
+    fn calculator_error_keeps_state() {
+        let mut calc = Calculator::new();
+
+        calc.eval("5").expect("Test returns Err().");
+        calc.eval("1 / 0").expect_err("Test should returns Err().");
+
+        assert_eq!(calc.history().len(), 1);
+        assert_eq!(calc.last_result(), Some(&int(5)));
+    }
+
+    #[test]
+    fn calculator_assign_variable() {
+        let mut calc = Calculator::new();
+
+        calc.eval("x = 3 * 4").expect("Test returns Err().");
+        let result = calc.eval("x + 1");
+
+        assert_eq!(result.expect("Test returns Err()."), Some(int(13)));
+        assert_eq!(calc.environment().get("x"), Some(&int(12)));
+    }
+
+    #[test]
+    fn calculator_previous_result() {
+        let mut calc = Calculator::new();
+
+        calc.eval("6 * 7").expect("Test returns Err().");
+        let result = calc.eval("ans + 1");
+
+        assert_eq!(result.expect("Test returns Err()."), Some(int(43)));
+    }
+
+    #[test]
+    fn calculator_call_function() {
+        let mut calc = Calculator::new();
+
+        calc.eval("x = 18").expect("Test returns Err().");
+        let result = calc.eval("gcd(x, 12) + sqrt(16)");
+
+        assert_eq!(result.expect("Test returns Err()."), Some(int(10)));
+    }
+
+    #[test]
+    fn calculator_define_function() {
+        let mut calc = Calculator::new();
+
+        let result = calc.eval("f(x, y) = x ^ 2 + y");
+        assert_eq!(result.expect("Test returns Err()."), None);
+
+        let result = calc.eval("f(3, 4)");
+        assert_eq!(result.expect("Test returns Err()."), Some(int(13)));
+        assert_eq!(calc.history().len(), 1);
+    }
+
+    #[test]
+    fn calculator_neg_argument() {
+        let mut calc = Calculator::new();
+
+        calc.eval("f(x) = x * 2").expect("Test returns Err().");
+
+        assert_eq!(calc.eval("abs(-3)").expect("Test returns Err()."), Some(int(3)));
+        assert_eq!(calc.eval("f(-3)").expect("Test returns Err()."), Some(int(-6)));
+        assert_eq!(calc.eval("max(-1, -2)").expect("Test returns Err()."), Some(int(-1)));
+    }
+
+    #[test]
+    fn calculator_recursion_limit() {
+        let mut calc = Calculator::new();
+        calc.set_max_depth(8);
+
+        calc.eval("f(n) = f(n - 1)").expect("Test returns Err().");
+        let result = calc.eval("f(3)");
+
+        match result.expect_err("Test should returns Err().") {
+            CalcError::Execute(ExecuteError::RecursionLimit { .. }) => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn calculator_recursion_at_default_depth() {
+        let mut calc = Calculator::new();
+
+        calc.eval("s(n) = n == 0 ? 0 : n + s(n - 1)").expect("Test returns Err().");
+        let result = calc.eval("s(250)");
+
+        assert_eq!(result.expect("Test returns Err()."), Some(int(31375)));
+    }
+
+    #[test]
+    fn calculator_deep_body_at_default_depth() {
+        let mut calc = Calculator::new();
+        let body = format!("{}f(n - 1){}", "1 + (".repeat(120), ")".repeat(120));
+
+        calc.eval(&format!("f(n) = n == 0 ? 0 : {}", body)).expect("Test returns Err().");
+        let result = calc.eval("f(250)");
+
+        assert_eq!(result.expect("Test returns Err()."), Some(int(30000)));
+        assert_eq!(calc.environment().depth(), 0);
+    }
+
+    #[test]
+    fn calculator_long_sum() {
+        let mut calc = Calculator::new();
+        let result = calc.eval(&format!("1{}", " + 1".repeat(10_000)));
+
+        assert_eq!(result.expect("Test returns Err()."), Some(int(10_001)));
+    }
+
+    #[test]
+    fn calculator_recursion_at_configured_depth() {
+        let mut calc = Calculator::new();
+        calc.set_max_depth(100_000);
+
+        calc.eval("f(n) = n == 0 ? 0 : 1 + f(n - 1)").expect("Test returns Err().");
+        let result = calc.eval("f(5000)");
+
+        assert_eq!(result.expect("Test returns Err()."), Some(int(5000)));
+        match calc.eval("f(100000)").expect_err("Test should returns Err().") {
+            CalcError::Execute(ExecuteError::RecursionLimit { max_depth, .. }) => {
+                assert_eq!(max_depth, 100_000)
+            }
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+        assert_eq!(calc.environment().depth(), 0);
+    }
+
+    #[test]
+    fn calculator_max_depth_is_bounded() {
+        let mut calc = Calculator::new();
+        calc.set_max_depth(usize::MAX);
+
+        calc.eval("f(n) = f(n - 1) + 1").expect("Test returns Err().");
+        let result = calc.eval("f(3)");
+
+        assert_eq!(calc.max_depth(), MAX_DEPTH);
+        match result.expect_err("Test should returns Err().") {
+            CalcError::Execute(ExecuteError::RecursionLimit { max_depth, .. }) => {
+                assert_eq!(max_depth, MAX_DEPTH)
+            }
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn calculator_endless_recursion_at_default_depth() {
+        let mut calc = Calculator::new();
+
+        calc.eval("f(n) = f(n - 1) + 1").expect("Test returns Err().");
+        let result = calc.eval("f(3)");
+
+        match result.expect_err("Test should returns Err().") {
+            CalcError::Execute(ExecuteError::RecursionLimit { .. }) => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+        calc.eval("f(n) = n == 0 ? 0 : f(n - 1)").expect("Test returns Err().");
+        assert_eq!(calc.eval("f(150)").expect("Test returns Err()."), Some(int(0)));
+    }
+
+    #[test]
+    fn calculator_factorial_in_recursion() {
+        let mut calc = Calculator::new();
+
+        calc.eval("f(n) = n == 0 ? 1 : n * f(n - 1) + n! - n!").expect("Test returns Err().");
+        let result = calc.eval("f(20)");
+
+        let expected = calc.eval("20!").expect("Test returns Err().");
+        assert_eq!(result.expect("Test returns Err()."), expected);
+    }
+
+    #[test]
+    fn calculator_bigint_mode() {
+        let mut calc = Calculator::new();
+
+        calc.eval("2 ^ 64").expect_err("Test should returns Err().");
+
+        calc.set_mode(Mode::BigInt);
+        let result = calc.eval("2 ^ 64");
+
+        let expected = "18446744073709551616".parse::<BigInt>().unwrap();
+        assert_eq!(result.expect("Test returns Err()."), Some(Value::Integer(expected)));
+    }
+
+    #[test]
+    fn calculator_rational_mode() {
+        let mut calc = Calculator::new();
+        calc.set_mode(Mode::Rational);
+
+        let result = calc.eval("7 / 2");
+
+        let expected = BigRational::new(7.into(), 2.into());
+        assert_eq!(result.expect("Test returns Err()."), Some(Value::Rational(expected)));
+    }
+
+    #[test]
+    fn calculator_bitwise_precedence() {
+        let mut calc = Calculator::new();
+
+        assert_eq!(calc.eval("0xf0 | 0x0f & 0x3c").expect("Test returns Err()."), Some(int(0xfc)));
+        assert_eq!(calc.eval("1 + 1 << 2 xor 1").expect("Test returns Err()."), Some(int(9)));
+        assert_eq!(calc.eval("~-1 | 2^3").expect("Test returns Err()."), Some(int(8)));
+    }
+
+    #[test]
+    fn calculator_programmer_mode() {
+        let mut calc = Calculator::new();
+        calc.set_mode(Mode::Programmer(IntType::U8, Overflow::Wrapping));
+
+        let result = calc.eval("~0x0f << 1");
+
+        assert_eq!(result.expect("Test returns Err()."), Some(int(0xe0)));
+    }
+
+    #[test]
+    fn calculator_piecewise_function() {
+        let mut calc = Calculator::new();
+        calc.set_mode(Mode::Rational);
+
+        let tax = concat!(
+            "tax(x) = x <= 1000 ? x / 10",
+            " : x <= 5000 ? 100 + (x - 1000) / 5",
+            " : 900 + (x - 5000) / 2",
+        );
+        calc.eval(tax).expect("Test returns Err().");
+        let result = calc.eval("tax(800) + tax(2000) + tax(6000)");
+
+        let expected = BigRational::from_integer(1780.into());
+        assert_eq!(result.expect("Test returns Err()."), Some(Value::Rational(expected)));
+    }
+
+    #[test]
+    fn calculator_recursion_with_conditional() {
+        let mut calc = Calculator::new();
+
+        calc.eval("fib(n) = n < 2 ? n : fib(n - 1) + fib(n - 2)").expect("Test returns Err().");
+        let result = calc.eval("fib(15) == 610 && !(fib(1) != 1)");
+
+        assert_eq!(result.expect("Test returns Err()."), Some(Value::Bool(true)));
+    }
+
+    #[test]
+    fn calculator_combinatorics() {
+        let mut calc = Calculator::new();
+        calc.set_mode(Mode::Rational);
+
+        // Probability of a flush, straight flushes included, in a five-card hand.
+        let result = calc.eval("4 * choose(13, 5) / choose(52, 5) == 5148 / 2598960");
+        assert_eq!(result.expect("Test returns Err()."), Some(Value::Bool(true)));
+
+        let result = calc.eval("-3!^2 + perm(5, 2) * 2!! + (1 + 2)!");
+        let expected = BigRational::from_integer(10.into());
+        assert_eq!(result.expect("Test returns Err()."), Some(Value::Rational(expected)));
+    }
+
+    #[test]
+    fn calculator_custom_operator() {
+        let mut calc = Calculator::new();
+        calc.set_mode(Mode::Rational);
+
+        calc.eval("infix 5 left \"<+>\" (a, b) = (a + b) / 2").expect("Test returns Err().");
+        let result = calc.eval("1 <+> 2 <+> 4 * 2");
+
+        let expected = BigRational::new(19.into(), 4.into());
+        assert_eq!(result.expect("Test returns Err()."), Some(Value::Rational(expected)));
+    }
+
+    #[test]
+    fn calculator_custom_prefix_postfix_word() {
+        let mut calc = Calculator::new();
+
+        calc.eval("postfix 15 \"%%\" (x) = x * 100").expect("Test returns Err().");
+        calc.eval("prefix 13 \"twice\" (x) = 2 * x").expect("Test returns Err().");
+        calc.eval("infix 14 right \"pow\" (a, b) = b == 0 ? 1 : a * a pow (b - 1)")
+            .expect("Test returns Err().");
+        let result = calc.eval("twice 3%% + 2 pow 3 pow 2");
+
+        assert_eq!(result.expect("Test returns Err()."), Some(int(1112)));
+    }
+
+    #[test]
+    fn calculator_native_operator() {
+        let mut calc = Calculator::new();
+        let notation = Notation::infix("<?>", 9, Associativity::Left).expect("Test returns Err().");
+        calc.define_operator(notation, |args, _| match (&args[0], &args[1]) {
+            (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(a.max(b).clone())),
+            _ => Err(ExecuteError::type_mismatch("<?>", &args[0])),
+        })
+        .expect("Test returns Err().");
+
+        let result = calc.eval("1 + 5 <?> 3 * 3");
+
+        assert_eq!(result.expect("Test returns Err()."), Some(int(9)));
+    }
+
+    #[test]
+    fn calculator_native_word_operator_error() {
+        let mut calc = Calculator::new();
+        let infix = Notation::infix("avg", 9, Associativity::Left).expect("Test returns Err().");
+        let postfix = Notation::postfix("pct", 15).expect("Test returns Err().");
+        for notation in [infix, postfix] {
+            let symbol = notation.symbol().to_string();
+            calc.define_operator(notation, move |args, _| {
+                Err(ExecuteError::overflow(&symbol, args.to_vec()))
+            })
+            .expect("Test returns Err().");
+        }
+
+        let infix = calc.eval("1 avg 2").expect_err("Test should returns Err().");
+        let postfix = calc.eval("3 pct").expect_err("Test should returns Err().");
+
+        assert_eq!(infix.localize(::message::Lang::En), "Overflow: 1 avg 2");
+        assert_eq!(postfix.localize(::message::Lang::En), "Overflow: 3 pct");
+    }
+
+    #[test]
+    fn calculator_word_operator_naming_function_or_variable() {
+        let mut calc = Calculator::new();
+
+        calc.eval("x = 2").expect("Test returns Err().");
+        calc.eval("f(a) = a").expect("Test returns Err().");
+        for symbol in &["x", "f", "sqrt", "ans"] {
+            let result = calc.eval(&format!("infix 5 left \"{}\" (a, b) = a + b", symbol));
+            match result.expect_err("Test should returns Err().") {
+                CalcError::Execute(ExecuteError::NameTaken(ref name)) => assert_eq!(name, symbol),
+                e => panic!("Unexcepted error: {:?}", e),
+            }
+        }
+
+        let result = calc.eval("x + f(1) + sqrt(4) + ans");
+        assert_eq!(result.expect("Test returns Err()."), Some(int(7)));
+    }
+
+    #[test]
+    fn calculator_custom_operator_not_declared() {
+        let mut calc = Calculator::new();
+
+        let result = calc.eval("1 <+> 2");
+
+        assert!(result.is_err());
+    }
+}
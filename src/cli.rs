@@ -0,0 +1,263 @@
+use rcalc::Format;
+use rcalc::IntType;
+use rcalc::Lang;
+use rcalc::Mode;
+use rcalc::Overflow;
+use rcalc::format::MAX_DIGITS;
+use rcalc::message::Message;
+use rcalc::message::MessageKind;
+
+
+/// Exit code for invalid command-line arguments. See `CalcError::exit_code` for the others.
+pub const EXIT_USAGE: i32 = 2;
+
+/// How results and errors of batch evaluation are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Output {
+    /// Formatted values on stdout and messages on stderr.
+    #[default]
+    Text,
+    /// One JSON object per evaluated line on stdout, errors included.
+    Json,
+}
+
+impl Output {
+    pub fn from_name(name: &str) -> Option<Output> {
+        match name {
+            "text" => Some(Output::Text),
+            "json" => Some(Output::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Source of lines evaluated without the interactive prompt.
+#[derive(Debug, PartialEq)]
+pub enum Input {
+    Expression(String),
+    File(String),
+    Stdin,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Options {
+    pub mode: Mode,
+    pub format: Format,
+    /// Language given by `--lang`. `None` leaves the choice to the `LANG` environment variable.
+    pub lang: Option<Lang>,
+    pub quiet: bool,
+    pub output: Output,
+    /// Inputs in command-line order. Empty when the REPL should start.
+    pub inputs: Vec<Input>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run(Options),
+    Help,
+    Version,
+}
+
+/// Parses command-line arguments, excluding the program name.
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Command, Message> {
+    let mut options = Options::default();
+    let mut format = None;
+    let mut precision = None;
+    let mut wrapping = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            "-q" | "--quiet" => options.quiet = true,
+            "--bigint" => options.mode = Mode::BigInt,
+            "--rational" => options.mode = Mode::Rational,
+            "--float" => options.mode = Mode::Float,
+            "--int" => {
+                let name = value(&arg, args.next())?;
+                match IntType::from_name(&name) {
+                    Some(int) => options.mode = Mode::Programmer(int, Overflow::Checked),
+                    None => return Err(Message::new(MessageKind::UnknownIntType).arg(name)),
+                }
+            }
+            "--wrapping" => wrapping = true,
+            "--batch" => options.inputs.push(Input::Stdin),
+            "-e" | "--expr" => options.inputs.push(Input::Expression(value(&arg, args.next())?)),
+            "-f" | "--file" => match value(&arg, args.next())?.as_str() {
+                "-" => options.inputs.push(Input::Stdin),
+                path => options.inputs.push(Input::File(path.to_string())),
+            },
+            "--format" => format = Some(value(&arg, args.next())?),
+            "--precision" => {
+                let digits = value(&arg, args.next())?;
+                match digits.parse::<usize>() {
+                    Ok(n) if n <= MAX_DIGITS => precision = Some(n),
+                    _ => return Err(Message::new(MessageKind::InvalidDigits).arg(digits)),
+                }
+            }
+            "--lang" => {
+                let name = value(&arg, args.next())?;
+                match Lang::from_name(&name) {
+                    Some(lang) => options.lang = Some(lang),
+                    None => return Err(Message::new(MessageKind::UnknownLang).arg(name)),
+                }
+            }
+            "--output" => {
+                let name = value(&arg, args.next())?;
+                match Output::from_name(&name) {
+                    Some(output) => options.output = output,
+                    None => return Err(Message::new(MessageKind::UnknownOutput).arg(name)),
+                }
+            }
+            "--" => options.inputs.extend(args.by_ref().map(Input::Expression)),
+            _ if is_option(&arg) => return Err(Message::new(MessageKind::UnknownOption).arg(arg)),
+            _ => options.inputs.push(Input::Expression(arg)),
+        }
+    }
+
+    if wrapping {
+        let int = match options.mode {
+            Mode::Programmer(int, _) => int,
+            _ => IntType::I64,
+        };
+        options.mode = Mode::Programmer(int, Overflow::Wrapping);
+    }
+
+    options.format = match (format, precision) {
+        (None, None) => Format::default(),
+        (None, Some(digits)) => Format::Decimal(digits),
+        (Some(name), precision) => match Format::from_name(&name, precision) {
+            Some(format) => format,
+            None => return Err(Message::new(MessageKind::UnknownFormat).arg(name)),
+        },
+    };
+
+    Ok(Command::Run(options))
+}
+
+fn value(option: &str, value: Option<String>) -> Result<String, Message> {
+    value.ok_or_else(|| Message::new(MessageKind::MissingValue).arg(option))
+}
+
+/// Tells an option from an expression. Expressions such as `-1+2` start with a hyphen too.
+fn is_option(arg: &str) -> bool {
+    arg.starts_with('-') && arg.chars().nth(1).is_some_and(|c| c.is_ascii_alphabetic() || c == '-')
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        parse_args(args.iter().map(|arg| arg.to_string())).map_err(|message| message.to_string())
+    }
+
+    fn options(args: &[&str]) -> Options {
+        match parse(args) {
+            Ok(Command::Run(options)) => options,
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    //// parse_args ////
+
+    #[test]
+    fn parse_args_empty() {
+        assert_eq!(options(&[]), Options::default());
+    }
+
+    #[test]
+    fn parse_args_inputs_in_order() {
+        let options = options(&["-e", "1+2", "--file", "a.rc", "3 * 4", "-f", "-", "--batch"]);
+
+        assert_eq!(
+            options.inputs,
+            vec![
+                Input::Expression("1+2".to_string()),
+                Input::File("a.rc".to_string()),
+                Input::Expression("3 * 4".to_string()),
+                Input::Stdin,
+                Input::Stdin,
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_args_negative_expression() {
+        let options = options(&["-1 + 2", "--", "-x"]);
+
+        assert_eq!(
+            options.inputs,
+            vec![Input::Expression("-1 + 2".to_string()), Input::Expression("-x".to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_args_settings() {
+        let options = options(&["--rational", "--format", "decimal", "--precision", "3", "-q"]);
+
+        assert_eq!(options.mode, Mode::Rational);
+        assert_eq!(options.format, Format::Decimal(3));
+        assert!(options.quiet);
+    }
+
+    #[test]
+    fn parse_args_precision_implies_decimal() {
+        let options = options(&["--precision", "4"]);
+
+        assert_eq!(options.format, Format::Decimal(4));
+    }
+
+    #[test]
+    fn parse_args_programmer_mode() {
+        assert_eq!(
+            options(&["--int", "u8"]).mode,
+            Mode::Programmer(IntType::U8, Overflow::Checked)
+        );
+        assert_eq!(
+            options(&["--wrapping", "--int", "i16"]).mode,
+            Mode::Programmer(IntType::I16, Overflow::Wrapping)
+        );
+        assert_eq!(
+            options(&["--wrapping"]).mode,
+            Mode::Programmer(IntType::I64, Overflow::Wrapping)
+        );
+    }
+
+    #[test]
+    fn parse_args_lang() {
+        let options = options(&["--lang", "en"]);
+
+        assert_eq!(options.lang, Some(Lang::En));
+    }
+
+    #[test]
+    fn parse_args_output() {
+        let options = options(&["--output", "json", "-e", "1"]);
+
+        assert_eq!(options.output, Output::Json);
+    }
+
+    #[test]
+    fn parse_args_help_and_version() {
+        assert_eq!(parse(&["-e", "1", "--help"]), Ok(Command::Help));
+        assert_eq!(parse(&["-V"]), Ok(Command::Version));
+    }
+
+    #[test]
+    fn parse_args_errors() {
+        assert_eq!(parse(&["--file"]), Err("オプションに値が必要です: --file".to_string()));
+        assert_eq!(parse(&["--color"]), Err("不明なオプションです: --color".to_string()));
+        assert_eq!(parse(&["--format", "roman"]), Err("不明な出力形式です: roman".to_string()));
+        assert_eq!(parse(&["--precision", "x"]), Err("桁数が不正です: x".to_string()));
+        assert_eq!(
+            parse(&["--precision", "5000000000"]),
+            Err("桁数が不正です: 5000000000".to_string())
+        );
+        assert_eq!(parse(&["--lang", "fr"]), Err("不明な言語です: fr".to_string()));
+        assert_eq!(parse(&["--output", "xml"]), Err("不明な出力スタイルです: xml".to_string()));
+        assert_eq!(parse(&["--int", "u128"]), Err("不明な整数型です: u128".to_string()));
+    }
+}
@@ -4,8 +4,12 @@ use std::fmt;
 use std::result;
 
 use expression::error::ParseError;
+use expression::span::Span;
 use expression::token::error::TokenError;
 use execute::error::ExecuteError;
+use message::Lang;
+use message::Message;
+use message::MessageKind;
 
 #[derive(Debug)]
 pub enum CalcError {
@@ -17,18 +21,68 @@ pub enum CalcError {
 
 pub type Result<T> = result::Result<T, CalcError>;
 
-impl fmt::Display for CalcError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl CalcError {
+    /// Exit status of the `rcalc` command for this error.
+    ///
+    /// | Code | Error     |
+    /// |------|-----------|
+    /// | 3    | `Token`   |
+    /// | 4    | `Parse`   |
+    /// | 5    | `Execute` |
+    /// | 6    | `Io`      |
+    ///
+    /// 0 means success and 2 is used for invalid command-line arguments.
+    pub fn exit_code(&self) -> i32 {
+        match *self {
+            CalcError::Token(_) => 3,
+            CalcError::Parse(_) => 4,
+            CalcError::Execute(_) => 5,
+            CalcError::Io(_) => 6,
+        }
+    }
+
+    /// Stable identifier of the kind of this error, such as `division_by_zero`.
+    pub fn code(&self) -> &'static str {
         match *self {
-            CalcError::Io(ref err) => write!(f, "IO error:{}", err),
-            CalcError::Token(ref err) => write!(f, "{}", err),
-            CalcError::Parse(ref err) => write!(f, "{}", err),
-            CalcError::Execute(ref err) => write!(f, "{}", err),
+            CalcError::Io(_) => "io",
+            CalcError::Token(ref err) => err.code(),
+            CalcError::Parse(ref err) => err.code(),
+            CalcError::Execute(ref err) => err.code(),
         }
     }
+
+    /// Position in the input the error points at, if any.
+    pub fn span(&self) -> Option<Span> {
+        match *self {
+            CalcError::Token(ref err) => Some(err.span()),
+            CalcError::Parse(ref err) => Some(err.span()),
+            CalcError::Io(_) | CalcError::Execute(_) => None,
+        }
+    }
+
+    pub fn message(&self) -> Message {
+        match *self {
+            CalcError::Io(ref err) => Message::new(MessageKind::Io).arg(err),
+            CalcError::Token(ref err) => err.message(),
+            CalcError::Parse(ref err) => err.message(),
+            CalcError::Execute(ref err) => err.message(),
+        }
+    }
+
+    /// Message in `lang`.
+    pub fn localize(&self, lang: Lang) -> String {
+        self.message().render(lang)
+    }
+}
+
+impl fmt::Display for CalcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.localize(Lang::default()))
+    }
 }
 
 impl error::Error for CalcError {
+    #[allow(deprecated)]
     fn description(&self) -> &str {
         match *self {
             CalcError::Io(ref err) => err.description(),
@@ -38,7 +92,7 @@ impl error::Error for CalcError {
         }
     }
 
-    fn cause(&self) -> Option<&error::Error> {
+    fn cause(&self) -> Option<&dyn error::Error> {
         match *self {
             CalcError::Io(ref err) => Some(err),
             CalcError::Token(ref err) => Some(err),
@@ -0,0 +1,339 @@
+use std::cmp::Ordering;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::One;
+use num_traits::Signed;
+use num_traits::ToPrimitive;
+use num_traits::Zero;
+
+use execute::Mode;
+use execute::Overflow;
+use execute::combinatorics;
+use execute::error::ExecuteError;
+use execute::error::ExecuteResult;
+use execute::value::Value;
+
+
+/// Operands of a binary operation, converted to a common type.
+enum Operands {
+    Integer(BigInt, BigInt),
+    Rational(BigRational, BigRational),
+    Float(f64, f64),
+}
+
+/// Converts operands of `op` to a common type. Booleans are rejected.
+fn operands(op: &str, left: Value, right: Value) -> ExecuteResult<Operands> {
+    match (left, right) {
+        (found @ Value::Bool(_), _) | (_, found @ Value::Bool(_)) => {
+            Err(ExecuteError::type_mismatch(op, &found))
+        }
+        (Value::Integer(l), Value::Integer(r)) => Ok(Operands::Integer(l, r)),
+        (Value::Integer(l), Value::Rational(r)) => {
+            Ok(Operands::Rational(BigRational::from_integer(l), r))
+        }
+        (Value::Rational(l), Value::Integer(r)) => {
+            Ok(Operands::Rational(l, BigRational::from_integer(r)))
+        }
+        (Value::Rational(l), Value::Rational(r)) => Ok(Operands::Rational(l, r)),
+        (l, r) => Ok(Operands::Float(l.to_f64(), r.to_f64())),
+    }
+}
+
+/// Compares two numbers after converting them to a common type.
+pub fn compare(op: &str, left: &Value, right: &Value) -> ExecuteResult<Ordering> {
+    match operands(op, left.clone(), right.clone())? {
+        Operands::Integer(l, r) => Ok(l.cmp(&r)),
+        Operands::Rational(l, r) => Ok(l.cmp(&r)),
+        Operands::Float(l, r) => Ok(l.partial_cmp(&r).unwrap_or(Ordering::Equal)),
+    }
+}
+
+pub fn add(left: Value, right: Value, mode: Mode) -> ExecuteResult<Value> {
+    match operands("+", left, right)? {
+        Operands::Integer(l, r) => mode.check(&l + &r, "+", &l, &r),
+        Operands::Rational(l, r) => Ok(Value::Rational(l + r)),
+        Operands::Float(l, r) => check_float(l + r, "+", l, r),
+    }
+}
+
+pub fn sub(left: Value, right: Value, mode: Mode) -> ExecuteResult<Value> {
+    match operands("-", left, right)? {
+        Operands::Integer(l, r) => mode.check(&l - &r, "-", &l, &r),
+        Operands::Rational(l, r) => Ok(Value::Rational(l - r)),
+        Operands::Float(l, r) => check_float(l - r, "-", l, r),
+    }
+}
+
+pub fn mult(left: Value, right: Value, mode: Mode) -> ExecuteResult<Value> {
+    match operands("*", left, right)? {
+        Operands::Integer(l, r) => mode.check(&l * &r, "*", &l, &r),
+        Operands::Rational(l, r) => Ok(Value::Rational(l * r)),
+        Operands::Float(l, r) => check_float(l * r, "*", l, r),
+    }
+}
+
+pub fn div(left: Value, right: Value, mode: Mode) -> ExecuteResult<Value> {
+    match operands("/", left, right)? {
+        Operands::Integer(l, ref r) if r.is_zero() => {
+            Err(ExecuteError::division_by_zero(Value::Integer(l)))
+        }
+        Operands::Integer(l, r) => mode.check(&l / &r, "/", &l, &r),
+        Operands::Rational(l, ref r) if r.is_zero() => {
+            Err(ExecuteError::division_by_zero(Value::Rational(l)))
+        }
+        Operands::Rational(l, r) => Ok(Value::Rational(l / r)),
+        Operands::Float(l, 0.0) => Err(ExecuteError::division_by_zero(Value::Float(l))),
+        Operands::Float(l, r) => check_float(l / r, "/", l, r),
+    }
+}
+
+pub fn rem(left: Value, right: Value, mode: Mode) -> ExecuteResult<Value> {
+    match operands("%", left, right)? {
+        Operands::Integer(l, ref r) if r.is_zero() => {
+            Err(ExecuteError::division_by_zero(Value::Integer(l)))
+        }
+        Operands::Integer(l, r) => mode.check(&l % &r, "%", &l, &r),
+        Operands::Rational(l, ref r) if r.is_zero() => {
+            Err(ExecuteError::division_by_zero(Value::Rational(l)))
+        }
+        Operands::Rational(l, r) => Ok(Value::Rational(l % r)),
+        Operands::Float(l, 0.0) => Err(ExecuteError::division_by_zero(Value::Float(l))),
+        Operands::Float(l, r) => check_float(l % r, "%", l, r),
+    }
+}
+
+pub fn pow(left: Value, right: Value, mode: Mode) -> ExecuteResult<Value> {
+    // Booleans are rejected before a float exponent converts the base.
+    let exp = match (&left, right) {
+        (&Value::Bool(_), _) => return Err(ExecuteError::type_mismatch("^", &left)),
+        (_, right @ Value::Bool(_)) => return Err(ExecuteError::type_mismatch("^", &right)),
+        (_, Value::Integer(n)) => n,
+        (_, Value::Rational(ref r)) if r.is_integer() => r.to_integer(),
+        (_, right @ Value::Rational(_)) => {
+            return Err(ExecuteError::FractionalExponent(right));
+        }
+        (_, Value::Float(r)) => return float_pow(left.to_f64(), r),
+    };
+
+    match left {
+        Value::Integer(base) => {
+            if exp.is_negative() {
+                return Err(ExecuteError::NegativeExponent(exp));
+            }
+            if let Mode::Programmer(int, Overflow::Wrapping) = mode {
+                let modulus = BigInt::one() << int.bits();
+                return Ok(Value::Integer(int.wrap(&base.modpow(&exp, &modulus))));
+            }
+            let result = int_pow(&base, &exp, mode)
+                .ok_or_else(|| ExecuteError::integer_overflow("^", &base, &exp))?;
+            mode.check(result, "^", &base, &exp)
+        }
+        Value::Rational(base) => {
+            if exp.is_negative() && base.is_zero() {
+                return Err(ExecuteError::division_by_zero(Value::Integer(BigInt::one())));
+            }
+            let abs_exp = exp.abs();
+            let numer = int_pow(base.numer(), &abs_exp, mode);
+            let denom = int_pow(base.denom(), &abs_exp, mode);
+            match (numer, denom) {
+                (Some(numer), Some(denom)) => {
+                    let result = BigRational::new(numer, denom);
+                    if exp.is_negative() {
+                        Ok(Value::Rational(result.recip()))
+                    } else {
+                        Ok(Value::Rational(result))
+                    }
+                }
+                _ => {
+                    let operands = vec![Value::Rational(base), Value::Integer(exp)];
+                    Err(ExecuteError::overflow("^", operands))
+                }
+            }
+        }
+        Value::Float(base) => float_pow(base, Value::Integer(exp).to_f64()),
+        Value::Bool(_) => unreachable!("Boolean bases are rejected with the exponent."),
+    }
+}
+
+fn float_pow(base: f64, exp: f64) -> ExecuteResult<Value> {
+    if base == 0.0 && exp < 0.0 {
+        return Err(ExecuteError::division_by_zero(Value::Float(1.0)));
+    }
+    check_float(base.powf(exp), "^", base, exp)
+}
+
+pub fn neg(operand: Value, mode: Mode) -> ExecuteResult<Value> {
+    match operand {
+        Value::Integer(n) => match mode.fit(-&n) {
+            Some(result) => Ok(Value::Integer(result)),
+            None => Err(ExecuteError::overflow("-", vec![Value::Integer(n)])),
+        },
+        Value::Rational(r) => Ok(Value::Rational(-r)),
+        Value::Float(f) => Ok(Value::Float(-f)),
+        Value::Bool(_) => Err(ExecuteError::type_mismatch("-", &operand)),
+    }
+}
+
+pub fn bit_and(left: Value, right: Value, mode: Mode) -> ExecuteResult<Value> {
+    let (l, r) = integer_operands("&", left, right)?;
+    integer_result(&l & &r, "&", &[&l, &r], mode)
+}
+
+pub fn bit_or(left: Value, right: Value, mode: Mode) -> ExecuteResult<Value> {
+    let (l, r) = integer_operands("|", left, right)?;
+    integer_result(&l | &r, "|", &[&l, &r], mode)
+}
+
+pub fn bit_xor(left: Value, right: Value, mode: Mode) -> ExecuteResult<Value> {
+    let (l, r) = integer_operands("xor", left, right)?;
+    integer_result(&l ^ &r, "xor", &[&l, &r], mode)
+}
+
+/// Inverts every bit. Unsigned types invert only the bits of their width.
+pub fn bit_not(operand: Value, mode: Mode) -> ExecuteResult<Value> {
+    let n = match operand.to_integer() {
+        Some(n) => n,
+        None if matches!(operand, Value::Bool(_)) => {
+            return Err(ExecuteError::type_mismatch("~", &operand));
+        }
+        None => return Err(ExecuteError::not_integer("~", &[operand])),
+    };
+    let result = match mode.int_type() {
+        Some(int) => int.wrap(&!&n),
+        None => !&n,
+    };
+    integer_result(result, "~", &[&n], mode)
+}
+
+pub fn shl(left: Value, right: Value, mode: Mode) -> ExecuteResult<Value> {
+    let (l, r) = integer_operands("<<", left, right)?;
+    let count = shift_count("<<", &l, &r)?;
+
+    let result = if l.is_zero() || count <= mode.max_bits() {
+        &l << count
+    } else if mode.is_wrapping() {
+        BigInt::zero()
+    } else {
+        return Err(ExecuteError::integer_overflow("<<", &l, &r));
+    };
+    integer_result(result, "<<", &[&l, &r], mode)
+}
+
+pub fn factorial(operand: Value, mode: Mode) -> ExecuteResult<Value> {
+    let n = match operand.to_integer() {
+        Some(n) => n,
+        None if matches!(operand, Value::Bool(_)) => {
+            return Err(ExecuteError::type_mismatch("!", &operand));
+        }
+        None => return Err(ExecuteError::not_integer("!", &[operand])),
+    };
+    if n.is_negative() {
+        return Err(ExecuteError::out_of_domain("!", &[Value::Integer(n)]));
+    }
+
+    match combinatorics::factorial(&n, mode.exact_bits()) {
+        Some(result) => integer_result(result, "!", &[&n], mode),
+        None => Err(ExecuteError::overflow("!", vec![Value::Integer(n)])),
+    }
+}
+
+/// Arithmetic shift, which rounds toward negative infinity.
+pub fn shr(left: Value, right: Value, mode: Mode) -> ExecuteResult<Value> {
+    let (l, r) = integer_operands(">>", left, right)?;
+    let count = shift_count(">>", &l, &r)?;
+
+    integer_result(&l >> count.min(l.bits()), ">>", &[&l, &r], mode)
+}
+
+/// Logical shift, which shifts zeros into the bit pattern of the width of the mode.
+/// Modes without a width accept only non-negative values.
+pub fn logical_shr(left: Value, right: Value, mode: Mode) -> ExecuteResult<Value> {
+    let (l, r) = integer_operands(">>>", left, right)?;
+    let count = shift_count(">>>", &l, &r)?;
+
+    let result = match mode.int_type() {
+        Some(int) => int.wrap(&(int.to_bits(&l) >> count.min(u64::from(int.bits())))),
+        None if l.is_negative() => {
+            let operands = [Value::Integer(l), Value::Integer(r)];
+            return Err(ExecuteError::out_of_domain(">>>", &operands));
+        }
+        None => &l >> count.min(l.bits()),
+    };
+    integer_result(result, ">>>", &[&l, &r], mode)
+}
+
+/// Operands of a bitwise operator. Integral fractions and floats are accepted.
+fn integer_operands(op: &str, left: Value, right: Value) -> ExecuteResult<(BigInt, BigInt)> {
+    match (left.to_integer(), right.to_integer()) {
+        (Some(l), Some(r)) => Ok((l, r)),
+        _ => match (left, right) {
+            (found @ Value::Bool(_), _) | (_, found @ Value::Bool(_)) => {
+                Err(ExecuteError::type_mismatch(op, &found))
+            }
+            (left, right) => Err(ExecuteError::not_integer(op, &[left, right])),
+        },
+    }
+}
+
+/// Number of bits to shift `value` by. Counts too large for `u64` saturate.
+fn shift_count(op: &str, value: &BigInt, count: &BigInt) -> ExecuteResult<u64> {
+    if count.is_negative() {
+        let operands = [Value::Integer(value.clone()), Value::Integer(count.clone())];
+        return Err(ExecuteError::out_of_domain(op, &operands));
+    }
+    Ok(count.to_u64().unwrap_or(u64::MAX))
+}
+
+/// Converts the result of an integer operation to the type the mode uses for numbers.
+pub fn integer_result(
+    result: BigInt,
+    op: &str,
+    operands: &[&BigInt],
+    mode: Mode,
+) -> ExecuteResult<Value> {
+    let value = match mode {
+        Mode::Rational => Some(Value::Rational(BigRational::from_integer(result))),
+        Mode::Float => Some(Value::Float(Value::Integer(result).to_f64()))
+            .filter(|value| value.to_f64().is_finite()),
+        _ => mode.fit(result).map(Value::Integer),
+    };
+    value.ok_or_else(|| {
+        let operands = operands.iter().map(|&n| Value::Integer(n.clone())).collect();
+        ExecuteError::overflow(op, operands)
+    })
+}
+
+/// Infinite results are reported as overflow and NaN as out of domain.
+fn check_float(result: f64, op: &str, left: f64, right: f64) -> ExecuteResult<Value> {
+    if result.is_nan() {
+        Err(ExecuteError::not_a_number(op, vec![Value::Float(left), Value::Float(right)]))
+    } else if result.is_infinite() {
+        Err(ExecuteError::overflow(op, vec![Value::Float(left), Value::Float(right)]))
+    } else {
+        Ok(Value::Float(result))
+    }
+}
+
+/// Computes `base ^ exp` for a non-negative `exp`.
+/// Returns `None` when the result would be too large for `mode`.
+fn int_pow(base: &BigInt, exp: &BigInt, mode: Mode) -> Option<BigInt> {
+    if exp.is_zero() {
+        return Some(BigInt::one());
+    }
+    if base.is_zero() || base.is_one() {
+        return Some(base.clone());
+    }
+    if *base == -BigInt::one() {
+        let odd = (exp % 2u32).is_one();
+        return Some(if odd { base.clone() } else { BigInt::one() });
+    }
+
+    let exp = exp.to_u64()?;
+    let min_bits = (base.bits() - 1).checked_mul(exp)?;
+    if min_bits > mode.max_bits() {
+        return None;
+    }
+
+    Some(base.pow(exp as u32))
+}
@@ -0,0 +1,125 @@
+use std::f64::consts::LOG2_E;
+
+use num_bigint::BigInt;
+use num_traits::One;
+use num_traits::ToPrimitive;
+use num_traits::Zero;
+
+
+/// Upper bound of the number of factors multiplied for a factorial or a binomial coefficient.
+/// Larger computations are reported as overflow instead of running for minutes.
+const MAX_FACTORS: u64 = 100_000;
+
+/// `n!` for a non-negative `n`. Returns `None` when it would exceed `max_bits`.
+pub fn factorial(n: &BigInt, max_bits: u64) -> Option<BigInt> {
+    let n = n.to_u64().filter(|&n| n <= MAX_FACTORS)?;
+    // n! >= (n / e)^n
+    if n > 1 && (n as f64) * ((n as f64).log2() - LOG2_E) > max_bits as f64 {
+        return None;
+    }
+
+    Some(product(&BigInt::one(), n))
+}
+
+/// Number of ordered selections of `k` out of `n`, i.e. `n! / (n - k)!`.
+/// Both must be non-negative. Returns `None` when the result would exceed `max_bits`.
+pub fn perm(n: &BigInt, k: &BigInt, max_bits: u64) -> Option<BigInt> {
+    if k > n {
+        return Some(BigInt::zero());
+    }
+    let k = k.to_u64().filter(|&k| k <= MAX_FACTORS)?;
+    let low = n - k + 1u32;
+    // Every factor is at least `low`.
+    if k as f64 * low.bits().saturating_sub(1) as f64 > max_bits as f64 {
+        return None;
+    }
+
+    Some(product(&low, k))
+}
+
+/// Number of selections of `k` out of `n`, i.e. `n! / (k! (n - k)!)`.
+/// Both must be non-negative. Returns `None` when the result would exceed `max_bits`.
+pub fn choose(n: &BigInt, k: &BigInt, max_bits: u64) -> Option<BigInt> {
+    if k > n {
+        return Some(BigInt::zero());
+    }
+    // C(n, k) = C(n, n - k), and the smaller one needs fewer factors.
+    let k = ::std::cmp::min(k.clone(), n - k);
+    let k = k.to_u64().filter(|&k| k <= MAX_FACTORS)?;
+    // C(n, k) >= (n / k)^k
+    if k > 0 {
+        let ratio = n.to_f64().unwrap_or(f64::INFINITY) / k as f64;
+        if k as f64 * ratio.log2() > max_bits as f64 {
+            return None;
+        }
+    }
+
+    let low = n - k + 1u32;
+    Some(product(&low, k) / product(&BigInt::one(), k))
+}
+
+/// Product of the `count` integers from `low`.
+/// Halves are multiplied separately so that the operands of each multiplication have similar sizes.
+fn product(low: &BigInt, count: u64) -> BigInt {
+    match count {
+        0 => BigInt::one(),
+        1 => low.clone(),
+        _ => {
+            let half = count / 2;
+            product(low, half) * product(&(low + half), count - half)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn big(n: u64) -> BigInt {
+        BigInt::from(n)
+    }
+
+    //// factorial ////
+
+    #[test]
+    fn factorial_small() {
+        assert_eq!(factorial(&big(0), 64), Some(big(1)));
+        assert_eq!(factorial(&big(5), 64), Some(big(120)));
+        assert_eq!(factorial(&big(20), 64), Some(big(2432902008176640000)));
+    }
+
+    #[test]
+    fn factorial_too_large() {
+        assert_eq!(factorial(&big(100), 64), None);
+        assert_eq!(factorial(&big(MAX_FACTORS + 1), u64::MAX), None);
+    }
+
+    //// perm ////
+
+    #[test]
+    fn perm_values() {
+        assert_eq!(perm(&big(5), &big(2), 64), Some(big(20)));
+        assert_eq!(perm(&big(5), &big(0), 64), Some(big(1)));
+        assert_eq!(perm(&big(2), &big(5), 64), Some(big(0)));
+        assert_eq!(perm(&big(1 << 40), &big(2), 64), None);
+    }
+
+    //// choose ////
+
+    #[test]
+    fn choose_values() {
+        assert_eq!(choose(&big(52), &big(5), 64), Some(big(2598960)));
+        assert_eq!(choose(&big(60), &big(30), 64), Some(big(118264581564861424)));
+        assert_eq!(choose(&big(10), &big(10), 64), Some(big(1)));
+        assert_eq!(choose(&big(3), &big(4), 64), Some(big(0)));
+    }
+
+    #[test]
+    fn choose_large_n() {
+        let n = BigInt::from(10).pow(30u32);
+        let expected = (&n * (&n - 1u32)) / 2u32;
+        assert_eq!(choose(&n, &(&n - 2u32), 1 << 22), Some(expected));
+        assert_eq!(choose(&n, &big(3), 64), None);
+    }
+}
@@ -0,0 +1,167 @@
+use std::cmp;
+use std::collections::BTreeMap;
+use std::collections::btree_map;
+use std::fmt;
+use std::sync::Arc;
+
+use execute::Mode;
+use execute::error::ExecuteError;
+use execute::error::ExecuteResult;
+use execute::function;
+use execute::value::Value;
+use expression::Expression;
+use expression::operator::Fixity;
+use expression::operator::Notation;
+use expression::operator::OperatorTable;
+
+
+/// Default upper bound of nested calls of user-defined functions.
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// Highest upper bound of nested calls that can be set.
+/// Each call in progress keeps its scope in memory, which an endless recursion fills up to this.
+pub const MAX_DEPTH: usize = 100_000;
+
+/// Function defined in the session, such as `f(x, y) = x^2 + y`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserFunction {
+    pub params: Vec<String>,
+    pub body: Expression,
+}
+
+/// Operator implemented in Rust. Receives the values of the operands in order.
+pub type NativeOperator = Arc<dyn Fn(&[Value], Mode) -> ExecuteResult<Value> + Send + Sync>;
+
+/// Implementation of an operator declared at runtime.
+#[derive(Clone)]
+pub enum OperatorFunction {
+    /// Declared in the session, such as `infix 5 left "<+>" (a, b) = (a + b) / 2`.
+    User(UserFunction),
+    /// Registered by an embedding program.
+    Native(NativeOperator),
+}
+
+impl fmt::Debug for OperatorFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            OperatorFunction::User(ref function) => f.debug_tuple("User").field(function).finish(),
+            OperatorFunction::Native(_) => f.write_str("Native(..)"),
+        }
+    }
+}
+
+/// Named values and functions kept between evaluations.
+#[derive(Debug)]
+pub struct Environment {
+    variables: BTreeMap<String, Value>,
+    functions: BTreeMap<String, UserFunction>,
+    operators: OperatorTable,
+    operator_functions: BTreeMap<(Fixity, String), OperatorFunction>,
+    /// Parameters of the function calls in progress, innermost last.
+    scopes: Vec<BTreeMap<String, Value>>,
+    max_depth: usize,
+}
+
+impl Default for Environment {
+    fn default() -> Environment {
+        Environment {
+            variables: BTreeMap::new(),
+            functions: BTreeMap::new(),
+            operators: OperatorTable::new(),
+            operator_functions: BTreeMap::new(),
+            scopes: Vec::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+}
+
+impl Environment {
+    pub fn new() -> Environment {
+        Environment::default()
+    }
+
+    /// Looks up a parameter of the innermost call, then a variable.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.scopes
+            .last()
+            .and_then(|scope| scope.get(name))
+            .or_else(|| self.variables.get(name))
+    }
+
+    pub fn set<S: Into<String>>(&mut self, name: S, value: Value) {
+        self.variables.insert(name.into(), value);
+    }
+
+    /// Iterates over variables in name order.
+    pub fn variables(&self) -> btree_map::Iter<'_, String, Value> {
+        self.variables.iter()
+    }
+
+    pub fn function(&self, name: &str) -> Option<&UserFunction> {
+        self.functions.get(name)
+    }
+
+    pub fn define<S: Into<String>>(&mut self, name: S, function: UserFunction) {
+        self.functions.insert(name.into(), function);
+    }
+
+    /// Iterates over user-defined functions in name order.
+    pub fn functions(&self) -> btree_map::Iter<'_, String, UserFunction> {
+        self.functions.iter()
+    }
+
+    /// Operators declared so far, used to parse the following input.
+    pub fn operators(&self) -> &OperatorTable {
+        &self.operators
+    }
+
+    /// Declares an operator, replacing the one with the same symbol on the same side of an operand.
+    /// A word naming a function or a variable is rejected, since it would hide the name.
+    pub fn define_operator(
+        &mut self,
+        notation: Notation,
+        function: OperatorFunction,
+    ) -> ExecuteResult<()> {
+        let symbol = notation.symbol().to_string();
+        if function::find(&symbol).is_some()
+            || self.functions.contains_key(&symbol)
+            || self.variables.contains_key(&symbol)
+        {
+            return Err(ExecuteError::name_taken(&symbol));
+        }
+        if notation.fixity() != Fixity::Prefix {
+            self.operator_functions.remove(&(Fixity::Infix, symbol.clone()));
+            self.operator_functions.remove(&(Fixity::Postfix, symbol.clone()));
+        }
+        self.operator_functions.insert((notation.fixity(), symbol), function);
+        self.operators.declare(notation);
+        Ok(())
+    }
+
+    pub fn operator(&self, notation: &Notation) -> Option<&OperatorFunction> {
+        self.operator_functions.get(&(notation.fixity(), notation.symbol().to_string()))
+    }
+
+    /// Enters a function call whose parameters are bound to `params`.
+    pub(crate) fn push_scope(&mut self, params: BTreeMap<String, Value>) {
+        self.scopes.push(params);
+    }
+
+    pub(crate) fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Number of function calls in progress.
+    pub(crate) fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Sets the upper bound of nested calls. Values above `MAX_DEPTH` are lowered to it.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = cmp::min(max_depth, MAX_DEPTH);
+    }
+}
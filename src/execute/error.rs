@@ -2,33 +2,228 @@ use std::error;
 use std::fmt;
 use std::result;
 
-#[derive(Debug)]
+use num_bigint::BigInt;
+
+use execute::function::Arity;
+use execute::value::Value;
+use expression::operator;
+use expression::operator::Fixity;
+use message::Lang;
+use message::Message;
+use message::MessageKind;
+
+#[derive(Debug, PartialEq)]
 pub enum ExecuteError {
-    OutOfDef(String)
+    /// Division, remainder or negative power of zero, with the dividend.
+    DivisionByZero(Value),
+    NegativeExponent(BigInt),
+    FractionalExponent(Value),
+    /// Floating-point operation whose result is NaN.
+    /// `fixity` is the position of the operator `op`, or `None` for a function.
+    NotANumber { op: String, fixity: Option<Fixity>, operands: Vec<Value> },
+    /// Result of an operator or a function that does not fit in the mode.
+    Overflow { op: String, fixity: Option<Fixity>, operands: Vec<Value> },
+    /// Number that cannot be represented in the mode, such as a literal too large for `i64`.
+    /// A decimal literal is held as the nearest `f64`.
+    OutOfRange(Value),
+    OutOfDomain { function: String, fixity: Option<Fixity>, args: Vec<Value> },
+    /// Argument of a builtin function or operand of a bitwise operator that must be an integer.
+    NotInteger { function: String, fixity: Option<Fixity>, args: Vec<Value> },
+    /// Operand of an operator or argument of a function that has the wrong type,
+    /// such as a boolean used arithmetically.
+    TypeMismatch { op: String, found: Value },
+    UndefinedVariable(String),
+    UndefinedFunction(String),
+    ArgumentCount { function: String, expected: Arity, given: usize },
+    RecursionLimit { function: String, max_depth: usize },
+    /// Word operator whose symbol is the name of a function or a variable, which it would hide.
+    NameTaken(String),
 }
 
 impl ExecuteError {
-    pub fn out_of_def<S>(message: S) -> ExecuteError
-    where
-        S: Into<String>,
-    {
-        ExecuteError::OutOfDef(message.into())
+    pub fn not_a_number(op: &str, operands: Vec<Value>) -> ExecuteError {
+        let fixity = builtin_fixity(op, operands.len());
+        ExecuteError::NotANumber { op: op.to_string(), fixity, operands }
+    }
+
+    pub fn overflow(op: &str, operands: Vec<Value>) -> ExecuteError {
+        let fixity = builtin_fixity(op, operands.len());
+        ExecuteError::Overflow { op: op.to_string(), fixity, operands }
+    }
+
+    /// Overflow of a binary operator applied to integers.
+    pub fn integer_overflow(op: &str, left: &BigInt, right: &BigInt) -> ExecuteError {
+        let operands = vec![Value::Integer(left.clone()), Value::Integer(right.clone())];
+        ExecuteError::overflow(op, operands)
+    }
+
+    pub fn division_by_zero(dividend: Value) -> ExecuteError {
+        ExecuteError::DivisionByZero(dividend)
+    }
+
+    pub fn out_of_range(value: Value) -> ExecuteError {
+        ExecuteError::OutOfRange(value)
+    }
+
+    pub fn out_of_domain(function: &str, args: &[Value]) -> ExecuteError {
+        let fixity = builtin_fixity(function, args.len());
+        ExecuteError::OutOfDomain { function: function.to_string(), fixity, args: args.to_vec() }
+    }
+
+    pub fn not_integer(function: &str, args: &[Value]) -> ExecuteError {
+        let fixity = builtin_fixity(function, args.len());
+        ExecuteError::NotInteger { function: function.to_string(), fixity, args: args.to_vec() }
+    }
+
+    pub fn type_mismatch(op: &str, found: &Value) -> ExecuteError {
+        ExecuteError::TypeMismatch { op: op.to_string(), found: found.clone() }
+    }
+
+    pub fn undefined_variable(name: &str) -> ExecuteError {
+        ExecuteError::UndefinedVariable(name.to_string())
+    }
+
+    pub fn undefined_function(name: &str) -> ExecuteError {
+        ExecuteError::UndefinedFunction(name.to_string())
+    }
+
+    pub fn argument_count(function: &str, expected: Arity, given: usize) -> ExecuteError {
+        ExecuteError::ArgumentCount { function: function.to_string(), expected, given }
     }
 
-    pub fn get_message(&self) -> &str {
+    pub fn recursion_limit(function: &str, max_depth: usize) -> ExecuteError {
+        ExecuteError::RecursionLimit { function: function.to_string(), max_depth }
+    }
+
+    pub fn name_taken(name: &str) -> ExecuteError {
+        ExecuteError::NameTaken(name.to_string())
+    }
+
+    /// Renders the operation `symbol` in this error as an operator of `fixity`.
+    /// Errors raised by operators declared at runtime are created without knowing their position.
+    pub fn in_operator(mut self, symbol: &str, fixity: Fixity) -> ExecuteError {
+        match self {
+            ExecuteError::NotANumber { ref op, fixity: ref mut slot, .. }
+            | ExecuteError::Overflow { ref op, fixity: ref mut slot, .. }
+            | ExecuteError::OutOfDomain { function: ref op, fixity: ref mut slot, .. }
+            | ExecuteError::NotInteger { function: ref op, fixity: ref mut slot, .. }
+                if op == symbol =>
+            {
+                *slot = Some(fixity)
+            }
+            _ => (),
+        }
+        self
+    }
+
+    /// Stable identifier of the kind of this error.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            ExecuteError::DivisionByZero(_) => "division_by_zero",
+            ExecuteError::NegativeExponent(_) => "negative_exponent",
+            ExecuteError::FractionalExponent(_) => "fractional_exponent",
+            ExecuteError::NotANumber { .. } => "not_a_number",
+            ExecuteError::Overflow { .. } => "overflow",
+            ExecuteError::OutOfRange(_) => "out_of_range",
+            ExecuteError::OutOfDomain { .. } => "out_of_domain",
+            ExecuteError::NotInteger { .. } => "not_integer",
+            ExecuteError::TypeMismatch { .. } => "type_mismatch",
+            ExecuteError::UndefinedVariable(_) => "undefined_variable",
+            ExecuteError::UndefinedFunction(_) => "undefined_function",
+            ExecuteError::ArgumentCount { .. } => "argument_count",
+            ExecuteError::RecursionLimit { .. } => "recursion_limit",
+            ExecuteError::NameTaken(_) => "name_taken",
+        }
+    }
+
+    pub fn message(&self) -> Message {
         match *self {
-            ExecuteError::OutOfDef(ref message) => message,
+            ExecuteError::DivisionByZero(ref dividend) => {
+                Message::new(MessageKind::DivisionByZero).arg(dividend)
+            }
+            ExecuteError::NegativeExponent(_) => Message::new(MessageKind::NegativeExponent),
+            ExecuteError::FractionalExponent(_) => Message::new(MessageKind::FractionalExponent),
+            ExecuteError::NotANumber { ref op, fixity, ref operands } => {
+                Message::new(MessageKind::NotANumber).arg(operation(op, fixity, operands))
+            }
+            ExecuteError::Overflow { ref op, fixity, ref operands } => {
+                Message::new(MessageKind::Overflow).arg(operation(op, fixity, operands))
+            }
+            ExecuteError::OutOfRange(ref value) => Message::new(MessageKind::OutOfRange).arg(value),
+            ExecuteError::OutOfDomain { ref function, fixity, ref args } => {
+                Message::new(MessageKind::OutOfDomain).arg(operation(function, fixity, args))
+            }
+            ExecuteError::NotInteger { ref function, fixity, ref args } => {
+                Message::new(MessageKind::NotInteger).arg(operation(function, fixity, args))
+            }
+            ExecuteError::TypeMismatch { ref op, ref found } => {
+                Message::new(MessageKind::TypeMismatch).arg(op).arg(found).arg(found.type_name())
+            }
+            ExecuteError::UndefinedVariable(ref name) => {
+                Message::new(MessageKind::UndefinedVariable).arg(name)
+            }
+            ExecuteError::UndefinedFunction(ref name) => {
+                Message::new(MessageKind::UndefinedFunction).arg(name)
+            }
+            ExecuteError::ArgumentCount { ref function, expected, given } => {
+                let (kind, count) = match expected {
+                    Arity::Exact(n) => (MessageKind::ArgumentCount, n),
+                    Arity::AtLeast(n) => (MessageKind::ArgumentCountAtLeast, n),
+                };
+                Message::new(kind).arg(function).arg(count).arg(given)
+            }
+            ExecuteError::RecursionLimit { ref function, max_depth } => {
+                Message::new(MessageKind::RecursionLimit).arg(function).arg(max_depth)
+            }
+            ExecuteError::NameTaken(ref name) => Message::new(MessageKind::NameTaken).arg(name),
         }
     }
+
+    /// Message in `lang`.
+    pub fn localize(&self, lang: Lang) -> String {
+        self.message().render(lang)
+    }
+}
+
+/// Position of the built-in operator `op` applied to `count` operands, or `None` for a function.
+/// A unary `!` is the factorial, as the logical not raises no error naming it.
+fn builtin_fixity(op: &str, count: usize) -> Option<Fixity> {
+    let fixities: &[Fixity] = match count {
+        1 => &[Fixity::Postfix, Fixity::Prefix],
+        2 => &[Fixity::Infix],
+        _ => &[],
+    };
+    fixities.iter().cloned().find(|&fixity| operator::builtin(op, fixity).is_some())
+}
+
+/// Renders an operator where `fixity` places it, and a function as a call.
+/// Word operators are separated from their operands, and negative operands of symbols enclosed.
+fn operation(op: &str, fixity: Option<Fixity>, operands: &[Value]) -> String {
+    let word = op.chars().all(|c| c.is_alphanumeric() || c == '_');
+    let enclose = |operand: &Value| match operand.to_string() {
+        ref operand if !word && operand.starts_with('-') => format!("({})", operand),
+        operand => operand,
+    };
+    match (fixity, operands) {
+        (Some(Fixity::Infix), [left, right]) => format!("{} {} {}", left, op, right),
+        (Some(Fixity::Prefix), [operand]) if word => format!("{} {}", op, operand),
+        (Some(Fixity::Prefix), [operand]) => format!("{}{}", op, enclose(operand)),
+        (Some(Fixity::Postfix), [operand]) if word => format!("{} {}", operand, op),
+        (Some(Fixity::Postfix), [operand]) => format!("{}{}", enclose(operand), op),
+        _ => call(op, operands),
+    }
+}
+
+fn call(function: &str, args: &[Value]) -> String {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    format!("{}({})", function, args.join(", "))
 }
 
 pub type ExecuteResult<T> = result::Result<T, ExecuteError>;
 
 impl fmt::Display for ExecuteError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            ExecuteError::OutOfDef(ref message) => write!(f, "{}", message),
-        }
+        write!(f, "{}", self.message())
     }
 }
 
@@ -36,11 +231,24 @@ impl fmt::Display for ExecuteError {
 impl error::Error for ExecuteError {
     fn description(&self) -> &str {
         match *self {
-            ExecuteError::OutOfDef(_) => "Paren mismatch.",
+            ExecuteError::DivisionByZero(_) => "Division by zero.",
+            ExecuteError::NegativeExponent(_) => "Negative exponent.",
+            ExecuteError::FractionalExponent(_) => "Fractional exponent.",
+            ExecuteError::NotANumber { .. } => "Result is not a number.",
+            ExecuteError::Overflow { .. } => "Arithmetic overflow.",
+            ExecuteError::OutOfRange(_) => "Value out of range.",
+            ExecuteError::OutOfDomain { .. } => "Out of domain.",
+            ExecuteError::NotInteger { .. } => "Argument is not an integer.",
+            ExecuteError::TypeMismatch { .. } => "Type mismatch.",
+            ExecuteError::UndefinedVariable(_) => "Undefined variable.",
+            ExecuteError::UndefinedFunction(_) => "Undefined function.",
+            ExecuteError::ArgumentCount { .. } => "Wrong number of arguments.",
+            ExecuteError::RecursionLimit { .. } => "Function calls nested too deeply.",
+            ExecuteError::NameTaken(_) => "Operator symbol is a function or variable name.",
         }
     }
 
-    fn cause(&self) -> Option<&error::Error> {
+    fn cause(&self) -> Option<&dyn error::Error> {
         None
     }
 }
@@ -0,0 +1,414 @@
+use std::cmp::Ordering;
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::Signed;
+
+use execute::Mode;
+use execute::arith;
+use execute::combinatorics;
+use execute::error::ExecuteError;
+use execute::error::ExecuteResult;
+use execute::value::Value;
+
+
+/// Number of arguments a function accepts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Arity {
+    Exact(usize),
+    AtLeast(usize),
+}
+
+impl Arity {
+    pub fn accepts(self, count: usize) -> bool {
+        match self {
+            Arity::Exact(n) => count == n,
+            Arity::AtLeast(n) => count >= n,
+        }
+    }
+}
+
+/// Function provided by the calculator.
+pub struct Builtin {
+    pub name: &'static str,
+    pub arity: Arity,
+    /// Receives only numbers; `call` rejects booleans before it.
+    body: fn(&[Value], Mode) -> ExecuteResult<Value>,
+}
+
+impl Builtin {
+    pub fn call(&self, args: &[Value], mode: Mode) -> ExecuteResult<Value> {
+        if !self.arity.accepts(args.len()) {
+            return Err(ExecuteError::argument_count(self.name, self.arity, args.len()));
+        }
+        if let Some(arg) = args.iter().find(|arg| matches!(arg, Value::Bool(_))) {
+            return Err(ExecuteError::type_mismatch(self.name, arg));
+        }
+        (self.body)(args, mode)
+    }
+}
+
+static BUILTINS: &[Builtin] = &[
+    Builtin { name: "sqrt", arity: Arity::Exact(1), body: sqrt },
+    Builtin { name: "abs", arity: Arity::Exact(1), body: abs },
+    Builtin { name: "min", arity: Arity::AtLeast(1), body: min },
+    Builtin { name: "max", arity: Arity::AtLeast(1), body: max },
+    Builtin { name: "gcd", arity: Arity::AtLeast(1), body: gcd },
+    Builtin { name: "lcm", arity: Arity::AtLeast(1), body: lcm },
+    Builtin { name: "choose", arity: Arity::Exact(2), body: choose },
+    Builtin { name: "perm", arity: Arity::Exact(2), body: perm },
+    Builtin { name: "floor", arity: Arity::Exact(1), body: floor },
+    Builtin { name: "ceil", arity: Arity::Exact(1), body: ceil },
+    Builtin { name: "round", arity: Arity::Exact(1), body: round },
+    Builtin { name: "sin", arity: Arity::Exact(1), body: sin },
+    Builtin { name: "cos", arity: Arity::Exact(1), body: cos },
+    Builtin { name: "ln", arity: Arity::Exact(1), body: ln },
+    Builtin { name: "log", arity: Arity::Exact(2), body: log },
+];
+
+/// Looks up a built-in function by name.
+pub fn find(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|builtin| builtin.name == name)
+}
+
+
+fn sqrt(args: &[Value], _mode: Mode) -> ExecuteResult<Value> {
+    match args[0] {
+        Value::Integer(ref n) if n.is_negative() => Err(ExecuteError::out_of_domain("sqrt", args)),
+        Value::Integer(ref n) => match exact_sqrt(n) {
+            Some(root) => Ok(Value::Integer(root)),
+            None => float_result("sqrt", args, args[0].to_f64().sqrt()),
+        },
+        Value::Rational(ref r) if r.is_negative() => Err(ExecuteError::out_of_domain("sqrt", args)),
+        Value::Rational(ref r) => match (exact_sqrt(r.numer()), exact_sqrt(r.denom())) {
+            (Some(numer), Some(denom)) => Ok(Value::Rational(BigRational::new(numer, denom))),
+            _ => float_result("sqrt", args, args[0].to_f64().sqrt()),
+        },
+        _ => float_result("sqrt", args, args[0].to_f64().sqrt()),
+    }
+}
+
+fn exact_sqrt(n: &BigInt) -> Option<BigInt> {
+    let root = n.sqrt();
+    if &root * &root == *n { Some(root) } else { None }
+}
+
+fn abs(args: &[Value], mode: Mode) -> ExecuteResult<Value> {
+    match args[0] {
+        Value::Integer(ref n) => match mode.fit(n.abs()) {
+            Some(result) => Ok(Value::Integer(result)),
+            None => Err(ExecuteError::overflow("abs", vec![Value::Integer(n.clone())])),
+        },
+        Value::Rational(ref r) => Ok(Value::Rational(r.abs())),
+        ref value => Ok(Value::Float(value.to_f64().abs())),
+    }
+}
+
+fn min(args: &[Value], _mode: Mode) -> ExecuteResult<Value> {
+    select("min", args, Ordering::Less)
+}
+
+fn max(args: &[Value], _mode: Mode) -> ExecuteResult<Value> {
+    select("max", args, Ordering::Greater)
+}
+
+/// Returns the first argument that no other argument is `ordering` to.
+fn select(name: &str, args: &[Value], ordering: Ordering) -> ExecuteResult<Value> {
+    let mut selected = &args[0];
+    for arg in &args[1..] {
+        if arith::compare(name, arg, selected)? == ordering {
+            selected = arg;
+        }
+    }
+    Ok(selected.clone())
+}
+
+fn gcd(args: &[Value], mode: Mode) -> ExecuteResult<Value> {
+    let mut result = integer_arg("gcd", args, 0)?.abs();
+    for i in 1..args.len() {
+        result = result.gcd(&integer_arg("gcd", args, i)?);
+    }
+    mode.number(result)
+}
+
+fn lcm(args: &[Value], mode: Mode) -> ExecuteResult<Value> {
+    let mut result = integer_arg("lcm", args, 0)?.abs();
+    for i in 1..args.len() {
+        result = result.lcm(&integer_arg("lcm", args, i)?);
+    }
+    mode.number(result)
+}
+
+fn choose(args: &[Value], mode: Mode) -> ExecuteResult<Value> {
+    combination("choose", args, mode, combinatorics::choose)
+}
+
+fn perm(args: &[Value], mode: Mode) -> ExecuteResult<Value> {
+    combination("perm", args, mode, combinatorics::perm)
+}
+
+/// Counts selections of `k` out of `n` exactly. Both must be non-negative integers.
+fn combination(
+    name: &str,
+    args: &[Value],
+    mode: Mode,
+    count: fn(&BigInt, &BigInt, u64) -> Option<BigInt>,
+) -> ExecuteResult<Value> {
+    let (n, k) = (integer_arg(name, args, 0)?, integer_arg(name, args, 1)?);
+    if n.is_negative() || k.is_negative() {
+        return Err(ExecuteError::out_of_domain(name, args));
+    }
+
+    match count(&n, &k, mode.exact_bits()) {
+        Some(result) => arith::integer_result(result, name, &[&n, &k], mode),
+        None => Err(ExecuteError::overflow(name, vec![Value::Integer(n), Value::Integer(k)])),
+    }
+}
+
+/// Converts the `i`-th argument to an integer. Integral fractions and floats are accepted.
+fn integer_arg(name: &str, args: &[Value], i: usize) -> ExecuteResult<BigInt> {
+    args[i].to_integer().ok_or_else(|| ExecuteError::not_integer(name, args))
+}
+
+fn floor(args: &[Value], _mode: Mode) -> ExecuteResult<Value> {
+    match args[0] {
+        Value::Integer(ref n) => Ok(Value::Integer(n.clone())),
+        Value::Rational(ref r) => Ok(Value::Rational(r.floor())),
+        ref value => Ok(Value::Float(value.to_f64().floor())),
+    }
+}
+
+fn ceil(args: &[Value], _mode: Mode) -> ExecuteResult<Value> {
+    match args[0] {
+        Value::Integer(ref n) => Ok(Value::Integer(n.clone())),
+        Value::Rational(ref r) => Ok(Value::Rational(r.ceil())),
+        ref value => Ok(Value::Float(value.to_f64().ceil())),
+    }
+}
+
+/// Rounds half away from zero.
+fn round(args: &[Value], _mode: Mode) -> ExecuteResult<Value> {
+    match args[0] {
+        Value::Integer(ref n) => Ok(Value::Integer(n.clone())),
+        Value::Rational(ref r) => Ok(Value::Rational(r.round())),
+        ref value => Ok(Value::Float(value.to_f64().round())),
+    }
+}
+
+fn sin(args: &[Value], _mode: Mode) -> ExecuteResult<Value> {
+    float_result("sin", args, args[0].to_f64().sin())
+}
+
+fn cos(args: &[Value], _mode: Mode) -> ExecuteResult<Value> {
+    float_result("cos", args, args[0].to_f64().cos())
+}
+
+fn ln(args: &[Value], _mode: Mode) -> ExecuteResult<Value> {
+    let x = args[0].to_f64();
+    if x <= 0.0 {
+        return Err(ExecuteError::out_of_domain("ln", args));
+    }
+    float_result("ln", args, x.ln())
+}
+
+/// Logarithm of the first argument to the base of the second.
+fn log(args: &[Value], _mode: Mode) -> ExecuteResult<Value> {
+    let x = args[0].to_f64();
+    let base = args[1].to_f64();
+    if x <= 0.0 || base <= 0.0 || base == 1.0 {
+        return Err(ExecuteError::out_of_domain("log", args));
+    }
+    float_result("log", args, x.ln() / base.ln())
+}
+
+fn float_result(name: &str, args: &[Value], result: f64) -> ExecuteResult<Value> {
+    if result.is_nan() {
+        Err(ExecuteError::out_of_domain(name, args))
+    } else if result.is_infinite() {
+        Err(ExecuteError::overflow(name, args.to_vec()))
+    } else {
+        Ok(Value::Float(result))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn call(name: &str, args: Vec<Value>, mode: Mode) -> ExecuteResult<Value> {
+        find(name).expect("Function not found.").call(&args, mode)
+    }
+
+    fn int(n: i64) -> Value {
+        Value::Integer(n.into())
+    }
+
+    fn ratio(n: i64, d: i64) -> Value {
+        Value::Rational(BigRational::new(n.into(), d.into()))
+    }
+
+    //// find ////
+
+    #[test]
+    fn find_unknown() {
+        assert!(find("unknown").is_none());
+    }
+
+    //// call ////
+
+    #[test]
+    fn call_wrong_argument_count() {
+        let result = call("log", vec![int(8)], Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ref err @ ExecuteError::ArgumentCount { .. } => assert_eq!(
+                err.to_string(),
+                "引数の数が正しくありません: log() の引数は 2 個ですが、1 個が渡されました"
+            ),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn call_sqrt_exact() {
+        let result = call("sqrt", vec![int(144)], Mode::Int64);
+
+        assert_eq!(result.expect("Test returns Err()."), int(12));
+    }
+
+    #[test]
+    fn call_sqrt_inexact() {
+        let result = call("sqrt", vec![int(2)], Mode::Int64);
+
+        assert_eq!(result.expect("Test returns Err()."), Value::Float(2f64.sqrt()));
+    }
+
+    #[test]
+    fn call_sqrt_rational() {
+        let result = call("sqrt", vec![ratio(4, 9)], Mode::Rational);
+
+        assert_eq!(result.expect("Test returns Err()."), ratio(2, 3));
+    }
+
+    #[test]
+    fn call_sqrt_negative() {
+        let result = call("sqrt", vec![int(-1)], Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::OutOfDomain { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn call_abs_overflow() {
+        let result = call("abs", vec![int(i64::MIN)], Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::Overflow { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn call_min_max() {
+        let args = vec![int(3), ratio(1, 2), int(-2), Value::Float(7.5)];
+
+        assert_eq!(call("min", args.clone(), Mode::Int64).expect("Test returns Err()."), int(-2));
+        assert_eq!(
+            call("max", args, Mode::Int64).expect("Test returns Err()."),
+            Value::Float(7.5)
+        );
+    }
+
+    #[test]
+    fn call_gcd_lcm() {
+        let args = vec![int(12), int(-18), int(30)];
+
+        assert_eq!(call("gcd", args.clone(), Mode::Int64).expect("Test returns Err()."), int(6));
+        assert_eq!(call("lcm", args, Mode::Int64).expect("Test returns Err()."), int(180));
+    }
+
+    #[test]
+    fn call_gcd_not_integer() {
+        let result = call("gcd", vec![int(4), ratio(1, 2)], Mode::Rational);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::NotInteger { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn call_choose_perm() {
+        let args = vec![int(52), int(5)];
+
+        let choose = call("choose", args.clone(), Mode::Int64).expect("Test returns Err().");
+        assert_eq!(choose, int(2598960));
+        assert_eq!(call("perm", args, Mode::Int64).expect("Test returns Err()."), int(311875200));
+        let result = call("choose", vec![int(3), int(5)], Mode::Int64);
+        assert_eq!(result.expect("Test returns Err()."), int(0));
+    }
+
+    #[test]
+    fn call_choose_negative() {
+        let result = call("choose", vec![int(5), int(-1)], Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ref err @ ExecuteError::OutOfDomain { .. } => {
+                assert!(err.to_string().ends_with("choose(5, -1)"))
+            }
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn call_perm_overflow() {
+        let result = call("perm", vec![int(30), int(20)], Mode::Int64);
+
+        let operands = vec![int(30), int(20)];
+        assert_eq!(result, Err(ExecuteError::overflow("perm", operands)));
+    }
+
+    #[test]
+    fn call_rounding_rational() {
+        let x = vec![ratio(-5, 2)];
+
+        let floor = call("floor", x.clone(), Mode::Rational);
+        let ceil = call("ceil", x.clone(), Mode::Rational);
+        let round = call("round", x, Mode::Rational);
+
+        assert_eq!(floor.expect("Test returns Err()."), ratio(-3, 1));
+        assert_eq!(ceil.expect("Test returns Err()."), ratio(-2, 1));
+        assert_eq!(round.expect("Test returns Err()."), ratio(-3, 1));
+    }
+
+    #[test]
+    fn call_log() {
+        let result = call("log", vec![int(8), int(2)], Mode::Int64);
+
+        assert_eq!(result.expect("Test returns Err()."), Value::Float(3.0));
+    }
+
+    #[test]
+    fn call_log_base_one() {
+        let result = call("log", vec![int(8), int(1)], Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::OutOfDomain { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn call_ln_zero() {
+        let result = call("ln", vec![int(0)], Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::OutOfDomain { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::cmp::Ordering;
+
+use execute::arith;
+use execute::error::ExecuteError;
+use execute::error::ExecuteResult;
+use execute::value::Value;
+
+
+/// Converts a condition or an operand of a logical operator, which must be a boolean.
+pub fn boolean(op: &str, value: Value) -> ExecuteResult<bool> {
+    match value {
+        Value::Bool(b) => Ok(b),
+        value => Err(ExecuteError::type_mismatch(op, &value)),
+    }
+}
+
+pub fn not(operand: Value) -> ExecuteResult<Value> {
+    boolean("!", operand).map(|b| Value::Bool(!b))
+}
+
+pub fn eq(left: Value, right: Value) -> ExecuteResult<Value> {
+    equal("==", &left, &right).map(Value::Bool)
+}
+
+pub fn ne(left: Value, right: Value) -> ExecuteResult<Value> {
+    equal("!=", &left, &right).map(|equal| Value::Bool(!equal))
+}
+
+pub fn lt(left: Value, right: Value) -> ExecuteResult<Value> {
+    arith::compare("<", &left, &right).map(|ordering| Value::Bool(ordering == Ordering::Less))
+}
+
+pub fn le(left: Value, right: Value) -> ExecuteResult<Value> {
+    arith::compare("<=", &left, &right).map(|ordering| Value::Bool(ordering != Ordering::Greater))
+}
+
+pub fn gt(left: Value, right: Value) -> ExecuteResult<Value> {
+    arith::compare(">", &left, &right).map(|ordering| Value::Bool(ordering == Ordering::Greater))
+}
+
+pub fn ge(left: Value, right: Value) -> ExecuteResult<Value> {
+    arith::compare(">=", &left, &right).map(|ordering| Value::Bool(ordering != Ordering::Less))
+}
+
+/// Compares two booleans or two numbers. Numbers of different types are compared by value.
+fn equal(op: &str, left: &Value, right: &Value) -> ExecuteResult<bool> {
+    match (left, right) {
+        (&Value::Bool(l), &Value::Bool(r)) => Ok(l == r),
+        (found @ &Value::Bool(_), _) | (_, found @ &Value::Bool(_)) => {
+            Err(ExecuteError::type_mismatch(op, found))
+        }
+        _ => arith::compare(op, left, right).map(|ordering| ordering == Ordering::Equal),
+    }
+}
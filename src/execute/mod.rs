@@ -1,38 +1,1207 @@
+pub mod environment;
 pub mod error;
+pub mod value;
+mod arith;
+mod combinatorics;
+mod function;
+mod logic;
+
+pub use execute::function::Arity;
+
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::One;
 
 use expression::Expression;
+use expression::Statement;
+use expression::operator::Fixity;
+use execute::environment::Environment;
+use execute::environment::OperatorFunction;
+use execute::environment::NativeOperator;
+use execute::environment::UserFunction;
 use execute::error::ExecuteError;
 use execute::error::ExecuteResult;
+use execute::value::Value;
+
+/// Upper bound of the size of a power in bits.
+/// Larger results are reported as overflow instead of exhausting memory.
+const MAX_POW_BITS: u64 = 1 << 22;
+
+/// Upper bound of the decimal exponent of a literal in rational mode.
+const MAX_DECIMAL_EXPONENT: u64 = 100_000;
+
+/// Numeric backend used to evaluate expressions.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Mode {
+    /// 64-bit signed integers. Results out of range are reported as overflow.
+    #[default]
+    Int64,
+    /// Arbitrary-precision integers.
+    BigInt,
+    /// Exact fractions of arbitrary-precision integers.
+    Rational,
+    /// Double-precision floating-point numbers.
+    Float,
+    /// Fixed-width integers for bit manipulation. Results are shown in hex and binary too.
+    Programmer(IntType, Overflow),
+}
+
+/// Integer type of the programmer mode.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IntType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    I64,
+    U64,
+}
+
+/// What happens to a result that does not fit in an `IntType`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Overflow {
+    /// Reported as an error.
+    Checked,
+    /// Truncated to the width like two's complement arithmetic.
+    Wrapping,
+}
+
+impl IntType {
+    /// Converts a Rust-style type name such as `u8`.
+    pub fn from_name(name: &str) -> Option<IntType> {
+        match name {
+            "i8" => Some(IntType::I8),
+            "u8" => Some(IntType::U8),
+            "i16" => Some(IntType::I16),
+            "u16" => Some(IntType::U16),
+            "i32" => Some(IntType::I32),
+            "u32" => Some(IntType::U32),
+            "i64" => Some(IntType::I64),
+            "u64" => Some(IntType::U64),
+            _ => None,
+        }
+    }
+
+    pub fn bits(self) -> u32 {
+        match self {
+            IntType::I8 | IntType::U8 => 8,
+            IntType::I16 | IntType::U16 => 16,
+            IntType::I32 | IntType::U32 => 32,
+            IntType::I64 | IntType::U64 => 64,
+        }
+    }
+
+    pub fn is_signed(self) -> bool {
+        matches!(self, IntType::I8 | IntType::I16 | IntType::I32 | IntType::I64)
+    }
+
+    pub fn contains(self, value: &BigInt) -> bool {
+        self.wrap(value) == *value
+    }
+
+    /// Bit pattern of `value` in this width, as a non-negative integer.
+    pub fn to_bits(self, value: &BigInt) -> BigInt {
+        value.mod_floor(&(BigInt::one() << self.bits()))
+    }
+
+    /// Truncates `value` to this width and reinterprets the bits as this type.
+    pub fn wrap(self, value: &BigInt) -> BigInt {
+        let bits = self.to_bits(value);
+        if self.is_signed() && bits.bit(u64::from(self.bits() - 1)) {
+            bits - (BigInt::one() << self.bits())
+        } else {
+            bits
+        }
+    }
+}
+
+impl Mode {
+    /// Fixed width of integers, which `>>>` shifts zeros into.
+    fn int_type(self) -> Option<IntType> {
+        match self {
+            Mode::Int64 => Some(IntType::I64),
+            Mode::Programmer(int, _) => Some(int),
+            Mode::BigInt | Mode::Rational | Mode::Float => None,
+        }
+    }
+
+    fn is_wrapping(self) -> bool {
+        matches!(self, Mode::Programmer(_, Overflow::Wrapping))
+    }
+
+    /// Converts an integer result to the mode.
+    /// Returns `None` when it is out of range, unless the mode wraps it.
+    fn fit(self, value: BigInt) -> Option<BigInt> {
+        match self {
+            Mode::Programmer(int, Overflow::Wrapping) => Some(int.wrap(&value)),
+            _ => match self.int_type() {
+                Some(int) if !int.contains(&value) => None,
+                _ => Some(value),
+            },
+        }
+    }
 
-pub fn execute(expr: Expression) -> ExecuteResult<i64> {
-    match expr {
-        Expression::Number(n) => Ok(n),
-        Expression::Add(left, right) => Ok(execute(*left)? + execute(*right)?),
-        Expression::Sub(left, right) => Ok(execute(*left)? - execute(*right)?),
-        Expression::Mult(left, right) => Ok(execute(*left)? * execute(*right)?),
-        Expression::Div(left, right) => {
-            let right_result = execute(*right)?;
-            if right_result != 0 {
-                Ok(execute(*left)? / right_result)
-            } else {
-                Err(ExecuteError::out_of_def("ゼロ除算エラー"))
+    fn max_bits(self) -> u64 {
+        match self.int_type() {
+            Some(int) => u64::from(int.bits()),
+            None => MAX_POW_BITS,
+        }
+    }
+
+    /// Upper bound of an exact result in bits. Wrapping modes compute it before wrapping.
+    fn exact_bits(self) -> u64 {
+        if self.is_wrapping() { MAX_POW_BITS } else { self.max_bits() }
+    }
+
+    fn number(self, n: BigInt) -> ExecuteResult<Value> {
+        match self {
+            Mode::Rational => Ok(Value::Rational(BigRational::from_integer(n))),
+            Mode::Float => match Value::Integer(n) {
+                ref value if value.to_f64().is_finite() => Ok(Value::Float(value.to_f64())),
+                value => Err(ExecuteError::out_of_range(value)),
+            },
+            _ => match self.fit(n.clone()) {
+                Some(n) => Ok(Value::Integer(n)),
+                None => Err(ExecuteError::out_of_range(Value::Integer(n))),
+            },
+        }
+    }
+
+    /// Converts a decimal literal. It is exact in rational mode and floating-point otherwise.
+    /// The programmer mode has no fractions.
+    fn decimal(self, literal: &str) -> ExecuteResult<Value> {
+        let float = literal.parse::<f64>().unwrap_or(f64::NAN);
+        let out_of_range = || ExecuteError::out_of_range(Value::Float(float));
+        match self {
+            Mode::Rational => value::decimal_to_rational(literal, MAX_DECIMAL_EXPONENT)
+                .map(Value::Rational)
+                .ok_or_else(out_of_range),
+            Mode::Programmer(_, _) => Err(out_of_range()),
+            _ if float.is_finite() => Ok(Value::Float(float)),
+            _ => Err(out_of_range()),
+        }
+    }
+
+    fn check(
+        self,
+        result: BigInt,
+        op: &str,
+        left: &BigInt,
+        right: &BigInt,
+    ) -> ExecuteResult<Value> {
+        match self.fit(result) {
+            Some(result) => Ok(Value::Integer(result)),
+            None => Err(ExecuteError::integer_overflow(op, left, right)),
+        }
+    }
+}
+
+/// Executes a statement. Assignments and definitions are stored in `env`.
+/// Returns `None` for a function definition, which has no value.
+pub fn execute_statement(
+    stmt: Statement,
+    mode: Mode,
+    env: &mut Environment,
+) -> ExecuteResult<Option<Value>> {
+    match stmt {
+        Statement::Expression(expr) | Statement::Convert(expr, _) => {
+            execute(expr, mode, env).map(Some)
+        }
+        Statement::Assign(name, expr) => {
+            let value = execute(expr, mode, env)?;
+            env.set(name, value.clone());
+            Ok(Some(value))
+        }
+        Statement::Define(name, params, body) => {
+            env.define(name, UserFunction { params, body });
+            Ok(None)
+        }
+        Statement::DefineOperator(notation, params, body) => {
+            env.define_operator(notation, OperatorFunction::User(UserFunction { params, body }))?;
+            Ok(None)
+        }
+    }
+}
+
+/// Evaluates an expression. The steps left and the values computed so far are kept on stacks
+/// in the heap, so neither deep expressions nor deep calls exhaust the stack of the caller.
+pub fn execute(expr: Expression, mode: Mode, env: &mut Environment) -> ExecuteResult<Value> {
+    let depth = env.depth();
+    let result = evaluate(expr, mode, env);
+    // An error leaves the scopes of the calls it interrupted.
+    while env.depth() > depth {
+        env.pop_scope();
+    }
+    result
+}
+
+/// Part of an evaluation left to do.
+enum Step {
+    /// Evaluates an expression and pushes its value.
+    Evaluate(Expression),
+    /// Pops the right operand, then the left one, and pushes the result.
+    Binary(fn(Value, Value, Mode) -> ExecuteResult<Value>),
+    /// Like `Binary` for the comparisons, which work alike in every mode.
+    Comparison(fn(Value, Value) -> ExecuteResult<Value>),
+    Unary(fn(Value, Mode) -> ExecuteResult<Value>),
+    Not,
+    /// Pops the left operand of `&&` and evaluates the right one if it is true.
+    And(Expression),
+    /// Pops the left operand of `||` and evaluates the right one if it is false.
+    Or(Expression),
+    /// Pops the right operand of `&&` or `||` and pushes it back checked as a boolean.
+    Boolean(&'static str),
+    /// Pops the condition and evaluates one of the branches.
+    Branch(Expression, Expression),
+    /// Pops the arguments and calls a function named like the first field.
+    Call(String, Callee, usize),
+    /// Leaves the scope of a user-defined function, whose value stays pushed.
+    Return,
+}
+
+/// Function a `Step::Call` calls.
+enum Callee {
+    User(UserFunction),
+    Builtin(&'static function::Builtin),
+    /// Operator declared with a closure, and where it is written.
+    Native(NativeOperator, Fixity),
+}
+
+fn evaluate(expr: Expression, mode: Mode, env: &mut Environment) -> ExecuteResult<Value> {
+    let mut steps = vec![Step::Evaluate(expr)];
+    let mut values = Vec::new();
+
+    while let Some(step) = steps.pop() {
+        match step {
+            Step::Evaluate(expr) => {
+                if let Some(value) = expand(expr, mode, env, &mut steps)? {
+                    values.push(value);
+                }
             }
-        },
-        Expression::Mod(left, right) => {
-            let right_result = execute(*right)?;
-            if right_result != 0 {
-                Ok(execute(*left)? % right_result)
-            } else {
-                Err(ExecuteError::out_of_def("ゼロ除算エラー"))
+            Step::Binary(op) => {
+                let right = pop(&mut values);
+                let left = pop(&mut values);
+                values.push(op(left, right, mode)?);
             }
-        },
-        Expression::Pow(left, right) => {
-            let right_result = execute(*right)?;
-            if right_result >= 0 {
-                Ok(execute(*left)?.pow(right_result as u32))
-            } else {
-                Err(ExecuteError::out_of_def("指数が負です"))
+            Step::Comparison(op) => {
+                let right = pop(&mut values);
+                let left = pop(&mut values);
+                values.push(op(left, right)?);
+            }
+            Step::Unary(op) => {
+                let operand = pop(&mut values);
+                values.push(op(operand, mode)?);
+            }
+            Step::Not => {
+                let operand = pop(&mut values);
+                values.push(logic::not(operand)?);
+            }
+            Step::And(right) => {
+                if logic::boolean("&&", pop(&mut values))? {
+                    steps.push(Step::Boolean("&&"));
+                    steps.push(Step::Evaluate(right));
+                } else {
+                    values.push(Value::Bool(false));
+                }
+            }
+            Step::Or(right) => {
+                if logic::boolean("||", pop(&mut values))? {
+                    values.push(Value::Bool(true));
+                } else {
+                    steps.push(Step::Boolean("||"));
+                    steps.push(Step::Evaluate(right));
+                }
             }
+            Step::Boolean(op) => {
+                let operand = pop(&mut values);
+                values.push(Value::Bool(logic::boolean(op, operand)?));
+            }
+            Step::Branch(then, otherwise) => {
+                let branch = if logic::boolean("?:", pop(&mut values))? { then } else { otherwise };
+                steps.push(Step::Evaluate(branch));
+            }
+            Step::Call(name, callee, count) => {
+                let args = values.split_off(values.len() - count);
+                match callee {
+                    Callee::User(function) => {
+                        let body = enter(&name, function, args, env)?;
+                        steps.push(Step::Return);
+                        steps.push(Step::Evaluate(body));
+                    }
+                    Callee::Builtin(builtin) => values.push(builtin.call(&args, mode)?),
+                    Callee::Native(native, fixity) => {
+                        let result = native(&args, mode).map_err(|e| e.in_operator(&name, fixity));
+                        values.push(result?);
+                    }
+                }
+            }
+            Step::Return => env.pop_scope(),
+        }
+    }
+
+    Ok(pop(&mut values))
+}
+
+fn pop(values: &mut Vec<Value>) -> Value {
+    values.pop().expect("Every step before leaves a value.")
+}
+
+/// Returns the value of a literal or a variable. Otherwise schedules the steps evaluating `expr`.
+fn expand(
+    expr: Expression,
+    mode: Mode,
+    env: &Environment,
+    steps: &mut Vec<Step>,
+) -> ExecuteResult<Option<Value>> {
+    let (step, operands) = match expr {
+        Expression::Number(n) => return mode.number(n).map(Some),
+        Expression::Decimal(literal) => return mode.decimal(&literal).map(Some),
+        Expression::Variable(name) => match env.get(&name) {
+            Some(value) => return Ok(Some(value.clone())),
+            None => return Err(ExecuteError::undefined_variable(&name)),
         },
+        Expression::Add(left, right) => (Step::Binary(arith::add), vec![*left, *right]),
+        Expression::Sub(left, right) => (Step::Binary(arith::sub), vec![*left, *right]),
+        Expression::Mult(left, right) => (Step::Binary(arith::mult), vec![*left, *right]),
+        Expression::Div(left, right) => (Step::Binary(arith::div), vec![*left, *right]),
+        Expression::Mod(left, right) => (Step::Binary(arith::rem), vec![*left, *right]),
+        Expression::Pow(left, right) => (Step::Binary(arith::pow), vec![*left, *right]),
+        Expression::Neg(operand) => (Step::Unary(arith::neg), vec![*operand]),
+        Expression::BitAnd(left, right) => (Step::Binary(arith::bit_and), vec![*left, *right]),
+        Expression::BitOr(left, right) => (Step::Binary(arith::bit_or), vec![*left, *right]),
+        Expression::BitXor(left, right) => (Step::Binary(arith::bit_xor), vec![*left, *right]),
+        Expression::BitNot(operand) => (Step::Unary(arith::bit_not), vec![*operand]),
+        Expression::Factorial(operand) => (Step::Unary(arith::factorial), vec![*operand]),
+        Expression::Shl(left, right) => (Step::Binary(arith::shl), vec![*left, *right]),
+        Expression::Shr(left, right) => (Step::Binary(arith::shr), vec![*left, *right]),
+        Expression::LogicalShr(left, right) => {
+            (Step::Binary(arith::logical_shr), vec![*left, *right])
+        }
+        Expression::Eq(left, right) => (Step::Comparison(logic::eq), vec![*left, *right]),
+        Expression::Ne(left, right) => (Step::Comparison(logic::ne), vec![*left, *right]),
+        Expression::Lt(left, right) => (Step::Comparison(logic::lt), vec![*left, *right]),
+        Expression::Le(left, right) => (Step::Comparison(logic::le), vec![*left, *right]),
+        Expression::Gt(left, right) => (Step::Comparison(logic::gt), vec![*left, *right]),
+        Expression::Ge(left, right) => (Step::Comparison(logic::ge), vec![*left, *right]),
+        Expression::And(left, right) => (Step::And(*right), vec![*left]),
+        Expression::Or(left, right) => (Step::Or(*right), vec![*left]),
+        Expression::Not(operand) => (Step::Not, vec![*operand]),
+        Expression::Conditional(condition, then, otherwise) => {
+            (Step::Branch(*then, *otherwise), vec![*condition])
+        }
+        Expression::Call(name, args) => {
+            let callee = match env.function(&name) {
+                Some(function) => Callee::User(function.clone()),
+                None => function::find(&name)
+                    .map(Callee::Builtin)
+                    .ok_or_else(|| ExecuteError::undefined_function(&name))?,
+            };
+            (Step::Call(name, callee, args.len()), args)
+        }
+        Expression::Operator(notation, operands) => {
+            let callee = match env.operator(&notation) {
+                Some(OperatorFunction::User(function)) => Callee::User(function.clone()),
+                Some(OperatorFunction::Native(native)) => {
+                    Callee::Native(native.clone(), notation.fixity())
+                }
+                None => return Err(ExecuteError::undefined_function(notation.symbol())),
+            };
+            (Step::Call(notation.symbol().to_string(), callee, operands.len()), operands)
+        }
+    };
+
+    // The operands are evaluated in order, before the step taking their values.
+    steps.push(step);
+    steps.extend(operands.into_iter().rev().map(Step::Evaluate));
+    Ok(None)
+}
+
+/// Enters a call of `function`, binding its parameters to `args` in a new scope.
+/// Returns the body to evaluate in the scope.
+fn enter(
+    name: &str,
+    function: UserFunction,
+    args: Vec<Value>,
+    env: &mut Environment,
+) -> ExecuteResult<Expression> {
+    if function.params.len() != args.len() {
+        let arity = function::Arity::Exact(function.params.len());
+        return Err(ExecuteError::argument_count(name, arity, args.len()));
+    }
+    if env.depth() >= env.max_depth() {
+        return Err(ExecuteError::recursion_limit(name, env.max_depth()));
+    }
+
+    env.push_scope(function.params.into_iter().zip(args).collect());
+    Ok(function.body)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(expr: Expression, mode: Mode) -> ExecuteResult<Value> {
+        execute(expr, mode, &mut Environment::new())
+    }
+
+    fn num<N: Into<BigInt>>(n: N) -> Box<Expression> {
+        Box::new(Expression::Number(n.into()))
+    }
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    fn int<N: Into<BigInt>>(n: N) -> Value {
+        Value::Integer(n.into())
+    }
+
+    fn ratio(n: i64, d: i64) -> Value {
+        Value::Rational(BigRational::new(n.into(), d.into()))
+    }
+
+    //// execute ////
+
+    #[test]
+    fn execute_add() {
+        let result = run(Expression::Add(num(1), num(2)), Mode::Int64);
+
+        assert_eq!(result.expect("Test returns Err()."), int(3));
+    }
+
+    #[test]
+    fn execute_pow() {
+        let result = run(Expression::Pow(num(2), num(62)), Mode::Int64);
+
+        assert_eq!(result.expect("Test returns Err()."), int(4611686018427387904i64));
+    }
+
+    #[test]
+    fn execute_div_zero() {
+        let result = run(Expression::Div(num(1), num(0)), Mode::Int64);
+
+        assert_eq!(result, Err(ExecuteError::DivisionByZero(int(1))));
+    }
+
+    #[test]
+    fn execute_add_overflow() {
+        let result = run(Expression::Add(num(9223372036854775807i64), num(1)), Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ref err @ ExecuteError::Overflow { .. } => {
+                assert!(err.to_string().contains("9223372036854775807 + 1"))
+            }
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_overflow_operands() {
+        let result = run(Expression::Mult(num(i64::MAX), num(2)), Mode::Int64);
+
+        let operands = vec![int(i64::MAX), int(2)];
+        assert_eq!(result, Err(ExecuteError::overflow("*", operands)));
+    }
+
+    #[test]
+    fn execute_sub_overflow() {
+        let result = run(Expression::Sub(num(-9223372036854775808i64), num(1)), Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::Overflow { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_mult_overflow() {
+        let result = run(Expression::Mult(num(4294967296i64), num(4294967296i64)), Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::Overflow { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_div_overflow() {
+        let result = run(Expression::Div(num(-9223372036854775808i64), num(-1)), Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ref err @ ExecuteError::Overflow { .. } => {
+                assert!(err.to_string().contains("-9223372036854775808 / -1"))
+            }
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_mod_min_by_neg_one() {
+        let result = run(
+            Expression::Mod(num(-9223372036854775808i64), num(-1)),
+            Mode::Int64,
+        );
+
+        assert_eq!(result.expect("Test returns Err()."), int(0));
+    }
+
+    #[test]
+    fn execute_pow_overflow() {
+        let result = run(Expression::Pow(num(2), num(63)), Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::Overflow { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_pow_huge_exponent() {
+        let result = run(Expression::Pow(num(-1), num(4294967297i64)), Mode::Int64);
+
+        assert_eq!(result.expect("Test returns Err()."), int(-1));
+    }
+
+    #[test]
+    fn execute_pow_huge_exponent_overflow() {
+        let result = run(Expression::Pow(num(2), num(4294967296i64)), Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::Overflow { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_number_out_of_range() {
+        let result = run(Expression::Number(big("9223372036854775808")), Mode::Int64);
+
+        let expected = Value::Integer(big("9223372036854775808"));
+        assert_eq!(result, Err(ExecuteError::OutOfRange(expected)));
+    }
+
+    #[test]
+    fn execute_bigint_add_over_max() {
+        let result = run(
+            Expression::Add(num(9223372036854775807i64), num(1)),
+            Mode::BigInt,
+        );
+
+        let expected = Value::Integer(big("9223372036854775808"));
+        assert_eq!(result.expect("Test returns Err()."), expected);
+    }
+
+    #[test]
+    fn execute_bigint_sub_under_min() {
+        let result = run(
+            Expression::Sub(num(-9223372036854775808i64), num(9223372036854775807i64)),
+            Mode::BigInt,
+        );
+
+        let expected = Value::Integer(big("-18446744073709551615"));
+        assert_eq!(result.expect("Test returns Err()."), expected);
+    }
+
+    #[test]
+    fn execute_bigint_div_min_by_neg_one() {
+        let result = run(
+            Expression::Div(num(-9223372036854775808i64), num(-1)),
+            Mode::BigInt,
+        );
+
+        let expected = Value::Integer(big("9223372036854775808"));
+        assert_eq!(result.expect("Test returns Err()."), expected);
+    }
+
+    #[test]
+    fn execute_bigint_pow() {
+        let result = run(Expression::Pow(num(2), num(200)), Mode::BigInt);
+
+        assert_eq!(
+            result.expect("Test returns Err()."),
+            Value::Integer(big("1606938044258990275541962092341162602522202993782792835301376"))
+        );
+    }
+
+    #[test]
+    fn execute_bigint_pow_large_exponent() {
+        let result = run(Expression::Pow(num(3), num(100000)), Mode::BigInt);
+
+        let result = result.expect("Test returns Err().");
+        assert_eq!(result.to_string().len(), 47713);
+    }
+
+    #[test]
+    fn execute_bigint_pow_too_large() {
+        let result = run(Expression::Pow(num(2), num(1i64 << 40)), Mode::BigInt);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::Overflow { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_rational_div() {
+        let result = run(Expression::Div(num(7), num(2)), Mode::Rational);
+
+        assert_eq!(result.expect("Test returns Err()."), ratio(7, 2));
+    }
+
+    #[test]
+    fn execute_rational_normalize() {
+        let result = run(Expression::Div(num(6), num(-4)), Mode::Rational);
+
+        assert_eq!(result.expect("Test returns Err()."), ratio(-3, 2));
+    }
+
+    #[test]
+    fn execute_rational_div_zero() {
+        let result = run(Expression::Div(num(1), num(0)), Mode::Rational);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::DivisionByZero(_) => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_rational_mod() {
+        let result = run(
+            Expression::Mod(Box::new(Expression::Div(num(7), num(2))), num(2)),
+            Mode::Rational,
+        );
+
+        assert_eq!(result.expect("Test returns Err()."), ratio(3, 2));
+    }
+
+    #[test]
+    fn execute_rational_pow_negative_exponent() {
+        let result = run(
+            Expression::Pow(num(2), Box::new(Expression::Number((-3).into()))),
+            Mode::Rational,
+        );
+
+        assert_eq!(result.expect("Test returns Err()."), ratio(1, 8));
+    }
+
+    #[test]
+    fn execute_rational_pow_fraction() {
+        let result = run(
+            Expression::Pow(
+                Box::new(Expression::Div(num(-2), num(3))),
+                Box::new(Expression::Number((-3).into())),
+            ),
+            Mode::Rational,
+        );
+
+        assert_eq!(result.expect("Test returns Err()."), ratio(-27, 8));
+    }
+
+    #[test]
+    fn execute_rational_pow_zero_negative_exponent() {
+        let result = run(
+            Expression::Pow(num(0), Box::new(Expression::Number((-1).into()))),
+            Mode::Rational,
+        );
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::DivisionByZero(_) => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_rational_pow_non_integer_exponent() {
+        let result = run(
+            Expression::Pow(num(2), Box::new(Expression::Div(num(1), num(2)))),
+            Mode::Rational,
+        );
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::FractionalExponent(_) => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_decimal_in_integer_mode() {
+        let result = run(
+            Expression::Add(Box::new(Expression::Decimal("1.5".to_string())), num(1)),
+            Mode::Int64,
+        );
+
+        assert_eq!(result.expect("Test returns Err()."), Value::Float(2.5));
+    }
+
+    #[test]
+    fn execute_decimal_in_rational_mode() {
+        let result = run(Expression::Decimal("1.5e-1".to_string()), Mode::Rational);
+
+        assert_eq!(result.expect("Test returns Err()."), ratio(3, 20));
+    }
+
+    #[test]
+    fn execute_float_div() {
+        let result = run(Expression::Div(num(7), num(2)), Mode::Float);
+
+        assert_eq!(result.expect("Test returns Err()."), Value::Float(3.5));
+    }
+
+    #[test]
+    fn execute_float_pow_negative_exponent() {
+        let result = run(
+            Expression::Pow(num(2), Box::new(Expression::Number((-2).into()))),
+            Mode::Float,
+        );
+
+        assert_eq!(result.expect("Test returns Err()."), Value::Float(0.25));
+    }
+
+    #[test]
+    fn execute_float_div_zero() {
+        let result = run(Expression::Div(num(1), num(0)), Mode::Float);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::DivisionByZero(_) => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_float_infinity() {
+        let result = run(
+            Expression::Mult(
+                Box::new(Expression::Decimal("1e300".to_string())),
+                Box::new(Expression::Decimal("1e300".to_string())),
+            ),
+            Mode::Float,
+        );
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::Overflow { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_float_nan() {
+        let result = run(
+            Expression::Pow(
+                Box::new(Expression::Number((-8).into())),
+                Box::new(Expression::Decimal("0.5".to_string())),
+            ),
+            Mode::Float,
+        );
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::NotANumber { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_float_literal_too_large() {
+        let result = run(Expression::Decimal("1e999".to_string()), Mode::Float);
+
+        assert_eq!(result, Err(ExecuteError::OutOfRange(Value::Float(f64::INFINITY))));
+    }
+
+    #[test]
+    fn execute_neg() {
+        let result = run(Expression::Neg(num(5)), Mode::Int64);
+
+        assert_eq!(result.expect("Test returns Err()."), int(-5));
+    }
+
+    #[test]
+    fn execute_neg_overflow() {
+        let result = run(Expression::Neg(num(-9223372036854775808i64)), Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::Overflow { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_neg_rational() {
+        let result = run(
+            Expression::Neg(Box::new(Expression::Div(num(1), num(2)))),
+            Mode::Rational,
+        );
+
+        assert_eq!(result.expect("Test returns Err()."), ratio(-1, 2));
+    }
+
+    #[test]
+    fn execute_bitwise() {
+        let expr = Expression::BitOr(
+            Box::new(Expression::BitAnd(num(0b1100), num(0b1010))),
+            Box::new(Expression::BitXor(num(0b0110), num(0b0011))),
+        );
+
+        assert_eq!(run(expr, Mode::Int64).expect("Test returns Err()."), int(0b1101));
+        assert_eq!(run(Expression::BitNot(num(5)), Mode::Int64), Ok(int(-6)));
+        assert_eq!(run(Expression::BitAnd(num(-1), num(0xff)), Mode::BigInt), Ok(int(0xff)));
+    }
+
+    #[test]
+    fn execute_bitwise_not_integer() {
+        let expr = Expression::BitAnd(Box::new(Expression::Decimal("1.5".to_string())), num(2));
+
+        match run(expr, Mode::Int64).expect_err("Test should returns Err().") {
+            ref err @ ExecuteError::NotInteger { .. } => {
+                assert!(err.to_string().ends_with("1.5 & 2"))
+            }
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+
+        let decimal = |s: &str| Box::new(Expression::Decimal(s.to_string()));
+        let xor = Expression::BitXor(decimal("1.5"), num(2));
+        let not = Expression::BitNot(decimal("-1.5"));
+        for (expr, operation) in [(xor, "1.5 xor 2"), (not, "~(-1.5)")] {
+            let err = run(expr, Mode::Int64).expect_err("Test should returns Err().");
+            assert!(err.to_string().ends_with(operation), "{}", err);
+        }
+    }
+
+    #[test]
+    fn execute_bitwise_rational() {
+        let expr = Expression::BitOr(Box::new(Expression::Div(num(4), num(2))), num(1));
+
+        assert_eq!(run(expr, Mode::Rational).expect("Test returns Err()."), ratio(3, 1));
+    }
+
+    #[test]
+    fn execute_shift() {
+        assert_eq!(run(Expression::Shl(num(3), num(4)), Mode::Int64), Ok(int(48)));
+        assert_eq!(run(Expression::Shr(num(-5), num(1)), Mode::Int64), Ok(int(-3)));
+        assert_eq!(run(Expression::Shr(num(-5), num(1000)), Mode::Int64), Ok(int(-1)));
+        assert_eq!(run(Expression::LogicalShr(num(-1), num(60)), Mode::Int64), Ok(int(15)));
+        assert_eq!(run(Expression::LogicalShr(num(-1), num(0)), Mode::Int64), Ok(int(-1)));
+    }
+
+    #[test]
+    fn execute_shift_overflow() {
+        let result = run(Expression::Shl(num(1), num(63)), Mode::Int64);
+
+        let operands = vec![int(1), int(63)];
+        assert_eq!(result, Err(ExecuteError::overflow("<<", operands)));
+        assert_eq!(run(Expression::Shl(num(0), num(1i64 << 40)), Mode::Int64), Ok(int(0)));
+    }
+
+    #[test]
+    fn execute_shift_negative_count() {
+        let result = run(Expression::Shl(num(1), Box::new(Expression::Neg(num(1)))), Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ref err @ ExecuteError::OutOfDomain { .. } => {
+                assert!(err.to_string().ends_with("1 << -1"))
+            }
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_logical_shift_without_width() {
+        let result = run(Expression::LogicalShr(num(-8), num(1)), Mode::BigInt);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::OutOfDomain { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+        assert_eq!(run(Expression::LogicalShr(num(8), num(1)), Mode::BigInt), Ok(int(4)));
+    }
+
+    #[test]
+    fn execute_factorial() {
+        let factorial = |n| Expression::Factorial(num(n));
+
+        assert_eq!(run(factorial(0), Mode::Int64), Ok(int(1)));
+        assert_eq!(run(factorial(20), Mode::Int64), Ok(int(2432902008176640000i64)));
+        assert_eq!(run(factorial(25), Mode::BigInt), Ok(int(big("15511210043330985984000000"))));
+        assert_eq!(run(factorial(5), Mode::Rational), Ok(ratio(120, 1)));
+        let wrapping = Mode::Programmer(IntType::U8, Overflow::Wrapping);
+        assert_eq!(run(factorial(6), wrapping), Ok(int(208)));
+    }
+
+    #[test]
+    fn execute_factorial_overflow() {
+        let result = run(Expression::Factorial(num(21)), Mode::Int64);
+
+        let operands = vec![int(21)];
+        let fixity = Some(Fixity::Postfix);
+        assert_eq!(result, Err(ExecuteError::Overflow { op: "!".to_string(), fixity, operands }));
+        match run(Expression::Factorial(num(1u64 << 40)), Mode::BigInt) {
+            Err(ExecuteError::Overflow { .. }) => (),
+            result => panic!("Unexcepted result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn execute_factorial_negative() {
+        let result = run(Expression::Factorial(Box::new(Expression::Neg(num(3)))), Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ref err @ ExecuteError::OutOfDomain { .. } => {
+                assert!(err.to_string().ends_with("(-3)!"))
+            }
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_factorial_not_integer() {
+        let half = Box::new(Expression::Decimal("2.5".to_string()));
+
+        match run(Expression::Factorial(half), Mode::Rational) {
+            Err(ExecuteError::NotInteger { .. }) => (),
+            result => panic!("Unexcepted result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn execute_programmer_checked() {
+        let mode = Mode::Programmer(IntType::U8, Overflow::Checked);
+
+        assert_eq!(run(Expression::Add(num(200), num(55)), mode), Ok(int(255)));
+        assert_eq!(run(Expression::BitNot(num(0x0f)), mode), Ok(int(0xf0)));
+        match run(Expression::Add(num(200), num(56)), mode) {
+            Err(ExecuteError::Overflow { .. }) => (),
+            result => panic!("Unexcepted result: {:?}", result),
+        }
+        match run(Expression::Number(256.into()), mode) {
+            Err(ExecuteError::OutOfRange(_)) => (),
+            result => panic!("Unexcepted result: {:?}", result),
+        }
+        assert_eq!(
+            run(Expression::Decimal("1.5".to_string()), mode),
+            Err(ExecuteError::OutOfRange(Value::Float(1.5)))
+        );
+    }
+
+    #[test]
+    fn execute_programmer_wrapping() {
+        let u8_mode = Mode::Programmer(IntType::U8, Overflow::Wrapping);
+        let i16_mode = Mode::Programmer(IntType::I16, Overflow::Wrapping);
+
+        assert_eq!(run(Expression::Add(num(200), num(56)), u8_mode), Ok(int(0)));
+        assert_eq!(run(Expression::Neg(num(1)), u8_mode), Ok(int(255)));
+        assert_eq!(run(Expression::Shl(num(1), num(100)), u8_mode), Ok(int(0)));
+        assert_eq!(run(Expression::Pow(num(3), num(5)), u8_mode), Ok(int(243)));
+        assert_eq!(run(Expression::Pow(num(3), num(6)), u8_mode), Ok(int(217)));
+        assert_eq!(run(Expression::Number(0xffff.into()), i16_mode), Ok(int(-1)));
+        assert_eq!(run(Expression::Mult(num(256), num(128)), i16_mode), Ok(int(-32768)));
+        assert_eq!(run(Expression::LogicalShr(num(-1), num(8)), i16_mode), Ok(int(0xff)));
+    }
+
+    #[test]
+    fn execute_comparison() {
+        let half = Box::new(Expression::Decimal("0.5".to_string()));
+
+        assert_eq!(run(Expression::Lt(num(1), num(2)), Mode::Int64), Ok(Value::Bool(true)));
+        assert_eq!(run(Expression::Ge(num(1), num(2)), Mode::Int64), Ok(Value::Bool(false)));
+        assert_eq!(
+            run(Expression::Eq(Box::new(Expression::Div(num(1), num(2))), half), Mode::Rational),
+            Ok(Value::Bool(true))
+        );
+        let not_equal = Expression::Ne(
+            Box::new(Expression::Lt(num(1), num(2))),
+            Box::new(Expression::Gt(num(1), num(2))),
+        );
+        assert_eq!(run(not_equal, Mode::Int64), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn execute_logic_short_circuit() {
+        let fail = || Box::new(Expression::Div(num(1), num(0)));
+        let yes = || Box::new(Expression::Le(num(1), num(1)));
+        let no = || Box::new(Expression::Not(yes()));
+
+        assert_eq!(run(Expression::And(no(), fail()), Mode::Int64), Ok(Value::Bool(false)));
+        assert_eq!(run(Expression::Or(yes(), fail()), Mode::Int64), Ok(Value::Bool(true)));
+        assert_eq!(run(Expression::Conditional(no(), fail(), num(7)), Mode::Int64), Ok(int(7)));
+        assert_eq!(
+            run(Expression::And(yes(), fail()), Mode::Int64),
+            Err(ExecuteError::DivisionByZero(int(1)))
+        );
+    }
+
+    #[test]
+    fn execute_boolean_arithmetic() {
+        let yes = Box::new(Expression::Eq(num(1), num(1)));
+        let result = run(Expression::Add(yes, num(1)), Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ref err @ ExecuteError::TypeMismatch { .. } => assert_eq!(
+                err.localize(::message::Lang::En),
+                "Type mismatch: + cannot be applied to true of type boolean"
+            ),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_boolean_power_in_float_mode() {
+        let yes = || Box::new(Expression::Lt(num(1), num(2)));
+        let found = Value::Bool(true);
+        let mismatch = Err(ExecuteError::TypeMismatch { op: "^".to_string(), found });
+
+        assert_eq!(run(Expression::Pow(yes(), num(2)), Mode::Float), mismatch);
+        assert_eq!(run(Expression::Pow(num(2), yes()), Mode::Float), mismatch);
+        assert_eq!(run(Expression::Pow(yes(), yes()), Mode::Int64), mismatch);
+    }
+
+    #[test]
+    fn execute_type_mismatch() {
+        let yes = || Box::new(Expression::Eq(num(1), num(1)));
+        let mismatch = |op: &str, found: Value| {
+            Err(ExecuteError::TypeMismatch { op: op.to_string(), found })
+        };
+
+        let conditional = Expression::Conditional(num(1), num(2), num(3));
+        assert_eq!(run(conditional, Mode::Int64), mismatch("?:", int(1)));
+        assert_eq!(run(Expression::Or(num(0), yes()), Mode::Int64), mismatch("||", int(0)));
+        let t = Value::Bool(true);
+        assert_eq!(run(Expression::Eq(yes(), num(1)), Mode::Int64), mismatch("==", t.clone()));
+        assert_eq!(run(Expression::Ne(num(1), yes()), Mode::Int64), mismatch("!=", t.clone()));
+        assert_eq!(run(Expression::Lt(yes(), yes()), Mode::Int64), mismatch("<", t.clone()));
+        assert_eq!(run(Expression::BitNot(yes()), Mode::Int64), mismatch("~", t.clone()));
+        assert_eq!(
+            run(Expression::Call("sqrt".to_string(), vec![*yes()]), Mode::Int64),
+            mismatch("sqrt", t)
+        );
+    }
+
+    //// IntType ////
+
+    #[test]
+    fn int_type_wrap() {
+        assert_eq!(IntType::I8.wrap(&128.into()), BigInt::from(-128));
+        assert_eq!(IntType::U16.wrap(&(-1).into()), BigInt::from(0xffff));
+        assert_eq!(IntType::U64.wrap(&big("18446744073709551616")), BigInt::from(0));
+        assert!(IntType::I32.contains(&(-2147483648i64).into()));
+        assert!(!IntType::U32.contains(&(-1).into()));
+    }
+
+    //// variables and calls ////
+
+    #[test]
+    fn execute_variable() {
+        let mut env = Environment::new();
+        env.set("x", int(12));
+
+        let expr = Expression::Mult(Box::new(Expression::Variable("x".to_string())), num(2));
+        let result = execute(expr, Mode::Int64, &mut env);
+
+        assert_eq!(result.expect("Test returns Err()."), int(24));
+    }
+
+    #[test]
+    fn execute_undefined_variable() {
+        let result = run(Expression::Variable("y".to_string()), Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::UndefinedVariable(name) => assert_eq!(name, "y"),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_call() {
+        let expr = Expression::Call("max".to_string(), vec![*num(2), Expression::Neg(num(5))]);
+        let result = run(Expression::Call("abs".to_string(), vec![expr]), Mode::Int64);
+
+        assert_eq!(result.expect("Test returns Err()."), int(2));
+    }
+
+    #[test]
+    fn execute_undefined_function() {
+        let result = run(Expression::Call("foo".to_string(), vec![*num(1)]), Mode::Int64);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::UndefinedFunction(name) => assert_eq!(name, "foo"),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    //// execute_statement ////
+
+    #[test]
+    fn execute_statement_assign() {
+        let mut env = Environment::new();
+
+        let stmt = Statement::Assign("x".to_string(), Expression::Add(num(1), num(2)));
+        let result = execute_statement(stmt, Mode::Int64, &mut env);
+
+        assert_eq!(result.expect("Test returns Err()."), Some(int(3)));
+        assert_eq!(env.get("x"), Some(&int(3)));
+    }
+
+    #[test]
+    fn execute_statement_failed_assign() {
+        let mut env = Environment::new();
+
+        let stmt = Statement::Assign("x".to_string(), Expression::Div(num(1), num(0)));
+        let result = execute_statement(stmt, Mode::Int64, &mut env);
+
+        assert!(result.is_err());
+        assert_eq!(env.get("x"), None);
+    }
+
+    #[test]
+    fn execute_statement_define_and_call() {
+        let mut env = Environment::new();
+        env.set("x", int(100));
+
+        let params = vec!["x".to_string(), "y".to_string()];
+        let body = Expression::Sub(
+            Box::new(Expression::Variable("x".to_string())),
+            Box::new(Expression::Variable("y".to_string())),
+        );
+        let stmt = Statement::Define("f".to_string(), params, body);
+        let result = execute_statement(stmt, Mode::Int64, &mut env);
+        assert_eq!(result.expect("Test returns Err()."), None);
+
+        let call = Expression::Call("f".to_string(), vec![*num(3), *num(4)]);
+        let result = execute(call, Mode::Int64, &mut env);
+
+        assert_eq!(result.expect("Test returns Err()."), int(-1));
+        assert_eq!(env.get("x"), Some(&int(100)));
+        assert_eq!(env.depth(), 0);
+    }
+
+    #[test]
+    fn execute_user_function_wrong_argument_count() {
+        let mut env = Environment::new();
+        env.define("f", UserFunction { params: vec!["x".to_string()], body: *num(1) });
+
+        let result = execute(Expression::Call("f".to_string(), vec![]), Mode::Int64, &mut env);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::ArgumentCount { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn execute_recursion_limit() {
+        let mut env = Environment::new();
+        env.set_max_depth(16);
+        let body = Expression::Call("f".to_string(), vec![]);
+        env.define("f", UserFunction { params: vec![], body });
+
+        let result = execute(Expression::Call("f".to_string(), vec![]), Mode::Int64, &mut env);
+
+        match result.expect_err("Test should returns Err().") {
+            ExecuteError::RecursionLimit { max_depth, .. } => assert_eq!(max_depth, 16),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+        assert_eq!(env.depth(), 0);
     }
 }
@@ -0,0 +1,144 @@
+use std::fmt;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::FromPrimitive;
+use num_traits::ToPrimitive;
+
+
+/// Result of evaluating an expression.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Integer(BigInt),
+    Rational(BigRational),
+    Float(f64),
+    /// Result of a comparison or a logical operator. Not a number.
+    Bool(bool),
+}
+
+impl Value {
+    /// Converts to the nearest `f64`. Values too large become infinity and booleans NaN.
+    pub fn to_f64(&self) -> f64 {
+        match *self {
+            Value::Integer(ref n) => n.to_f64().unwrap_or(f64::NAN),
+            Value::Rational(ref r) => r.to_f64().unwrap_or(f64::NAN),
+            Value::Float(f) => f,
+            Value::Bool(_) => f64::NAN,
+        }
+    }
+
+    /// Converts an integral value to an integer. Fractions and non-integral floats give `None`.
+    pub fn to_integer(&self) -> Option<BigInt> {
+        match *self {
+            Value::Integer(ref n) => Some(n.clone()),
+            Value::Rational(ref r) if r.is_integer() => Some(r.to_integer()),
+            Value::Rational(_) => None,
+            Value::Float(f) if f.fract() == 0.0 => BigInt::from_f64(f),
+            Value::Float(_) => None,
+            Value::Bool(_) => None,
+        }
+    }
+
+    /// Lowercase name of the variant, such as `integer`.
+    pub fn type_name(&self) -> &'static str {
+        match *self {
+            Value::Integer(_) => "integer",
+            Value::Rational(_) => "rational",
+            Value::Float(_) => "float",
+            Value::Bool(_) => "boolean",
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Integer(ref n) => write!(f, "{}", n),
+            Value::Rational(ref r) => write!(f, "{}", r),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Float(x) => {
+                let abs = x.abs();
+                if abs >= 1e16 || (abs != 0.0 && abs < 1e-6) {
+                    write!(f, "{:e}", x)
+                } else {
+                    write!(f, "{}", x)
+                }
+            }
+        }
+    }
+}
+
+/// Converts a decimal literal such as `6.02e23` to an exact fraction.
+/// Returns `None` when the exponent exceeds `max_exponent`.
+pub fn decimal_to_rational(literal: &str, max_exponent: u64) -> Option<BigRational> {
+    let (mantissa, exponent) = match literal.find(['e', 'E']) {
+        Some(i) => (&literal[..i], literal[i + 1..].parse::<i64>().ok()?),
+        None => (literal, 0),
+    };
+
+    let (whole, fract) = match mantissa.find('.') {
+        Some(i) => (&mantissa[..i], &mantissa[i + 1..]),
+        None => (mantissa, ""),
+    };
+
+    let digits = format!("{}{}", whole, fract).parse::<BigInt>().ok()?;
+    let exponent = exponent.checked_sub(fract.len() as i64)?;
+    if exponent.unsigned_abs() > max_exponent {
+        return None;
+    }
+
+    let scale = BigInt::from(10).pow(exponent.unsigned_abs() as u32);
+    if exponent >= 0 {
+        Some(BigRational::from_integer(digits * scale))
+    } else {
+        Some(BigRational::new(digits, scale))
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    //// decimal_to_rational ////
+
+    #[test]
+    fn decimal_to_rational_fraction() {
+        let r = decimal_to_rational("1.25", 100);
+        assert_eq!(r, Some(BigRational::new(5.into(), 4.into())));
+    }
+
+    #[test]
+    fn decimal_to_rational_scientific() {
+        let r = decimal_to_rational("6.02e23", 100);
+        let expected = BigInt::from(602) * BigInt::from(10).pow(21);
+        assert_eq!(r, Some(BigRational::from_integer(expected)));
+    }
+
+    #[test]
+    fn decimal_to_rational_negative_exponent() {
+        let r = decimal_to_rational(".5E-2", 100);
+        assert_eq!(r, Some(BigRational::new(1.into(), 200.into())));
+    }
+
+    #[test]
+    fn decimal_to_rational_too_large() {
+        assert_eq!(decimal_to_rational("1e101", 100), None);
+    }
+
+    //// Display ////
+
+    #[test]
+    fn display_float() {
+        assert_eq!(Value::Float(1.5).to_string(), "1.5");
+        assert_eq!(Value::Float(2.0).to_string(), "2");
+        assert_eq!(Value::Float(6.02e23).to_string(), "6.02e23");
+        assert_eq!(Value::Float(-1e-9).to_string(), "-1e-9");
+    }
+
+    #[test]
+    fn display_bool() {
+        assert_eq!(Value::Bool(true).to_string(), "true");
+        assert_eq!(Value::Bool(false).type_name(), "boolean");
+    }
+}
@@ -2,53 +2,114 @@ use std::error;
 use std::fmt;
 use std::result;
 
-#[derive(Debug)]
+use expression::span::Span;
+use message::Lang;
+use message::Message;
+use message::MessageKind;
+
+/// Error in the structure of an expression.
+/// Each variant holds the span of the offending token and the input it points into.
+#[derive(Debug, PartialEq)]
 pub enum ParseError {
-    MismatchParen(String),
-    NoToken(String),
-    TooMuchToken(String),
+    /// Parenthesis without its partner.
+    MismatchParen { span: Span, input: String },
+    /// Operand missing after the token at `span`.
+    NoToken { span: Span, input: String },
+    /// Token left over after a complete expression.
+    TooMuchToken { span: Span, input: String },
+    /// Token that cannot appear at `span`. The tokens that could are not recorded:
+    /// the operator table changes with declarations, so no fixed list would be accurate.
+    UnexpectedToken { span: Span, input: String },
+    /// Parentheses or operators nested beyond the limit, which the token at `span` exceeds.
+    TooDeep { span: Span, input: String },
 }
 
 impl ParseError {
-    pub fn mismatch_paren<S>(message: S) -> ParseError
-    where
-        S: Into<String>,
-    {
-        ParseError::MismatchParen(message.into())
+    pub fn mismatch_paren(span: Span, input: &str) -> ParseError {
+        ParseError::MismatchParen { span, input: input.to_string() }
+    }
+
+    pub fn no_token(span: Span, input: &str) -> ParseError {
+        ParseError::NoToken { span, input: input.to_string() }
     }
 
-    pub fn no_token<S>(message: S) -> ParseError
-    where
-        S: Into<String>,
-    {
-        ParseError::NoToken(message.into())
+    pub fn too_much_token(span: Span, input: &str) -> ParseError {
+        ParseError::TooMuchToken { span, input: input.to_string() }
     }
 
-    pub fn too_much_token<S>(message: S) -> ParseError
-    where
-        S: Into<String>,
-    {
-        ParseError::TooMuchToken(message.into())
+    pub fn unexpected_token(span: Span, input: &str) -> ParseError {
+        ParseError::UnexpectedToken { span, input: input.to_string() }
     }
 
-    pub fn get_message(&self) -> &str {
+    pub fn too_deep(span: Span, input: &str) -> ParseError {
+        ParseError::TooDeep { span, input: input.to_string() }
+    }
+
+    pub fn span(&self) -> Span {
         match *self {
-            ParseError::MismatchParen(ref message) => message,
-            ParseError::NoToken(ref message) => message,
-            ParseError::TooMuchToken(ref message) => message,
+            ParseError::MismatchParen { span, .. } => span,
+            ParseError::NoToken { span, .. } => span,
+            ParseError::TooMuchToken { span, .. } => span,
+            ParseError::UnexpectedToken { span, .. } => span,
+            ParseError::TooDeep { span, .. } => span,
         }
     }
+
+    /// Input the span points into.
+    pub fn input(&self) -> &str {
+        match *self {
+            ParseError::MismatchParen { ref input, .. } => input,
+            ParseError::NoToken { ref input, .. } => input,
+            ParseError::TooMuchToken { ref input, .. } => input,
+            ParseError::UnexpectedToken { ref input, .. } => input,
+            ParseError::TooDeep { ref input, .. } => input,
+        }
+    }
+
+    /// Text of the offending token. Empty at the end of the input.
+    pub fn found(&self) -> &str {
+        self.input().get(self.span().start..self.span().end).unwrap_or("")
+    }
+
+    /// Stable identifier of the kind of this error.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            ParseError::MismatchParen { .. } => "mismatch_paren",
+            ParseError::NoToken { .. } => "no_token",
+            ParseError::TooMuchToken { .. } => "too_much_token",
+            ParseError::UnexpectedToken { .. } => "unexpected_token",
+            ParseError::TooDeep { .. } => "too_deep",
+        }
+    }
+
+    pub fn message(&self) -> Message {
+        let (kind, label) = match *self {
+            ParseError::MismatchParen { .. } => {
+                (MessageKind::MismatchParen, MessageKind::MismatchParenLabel)
+            }
+            ParseError::NoToken { .. } => (MessageKind::NoToken, MessageKind::NoTokenLabel),
+            ParseError::TooMuchToken { .. } => {
+                (MessageKind::TooMuchToken, MessageKind::TooMuchTokenLabel)
+            }
+            ParseError::UnexpectedToken { .. } => {
+                (MessageKind::UnexpectedToken, MessageKind::UnexpectedTokenLabel)
+            }
+            ParseError::TooDeep { .. } => (MessageKind::TooDeep, MessageKind::TooDeepLabel),
+        };
+        Message::new(kind).annotate(self.span(), self.input(), label)
+    }
+
+    /// Message in `lang`.
+    pub fn localize(&self, lang: Lang) -> String {
+        self.message().render(lang)
+    }
 }
 
 pub type ParseResult<T> = result::Result<T, ParseError>;
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            ParseError::MismatchParen(ref message) => write!(f, "{}", message),
-            ParseError::NoToken(ref message) => write!(f, "{}", message),
-            ParseError::TooMuchToken(ref message) => write!(f, "{}", message),
-        }
+        write!(f, "{}", self.message())
     }
 }
 
@@ -56,13 +117,15 @@ impl fmt::Display for ParseError {
 impl error::Error for ParseError {
     fn description(&self) -> &str {
         match *self {
-            ParseError::MismatchParen(_) => "Paren mismatch.",
-            ParseError::NoToken(_) => "Tokens end while parse",
-            ParseError::TooMuchToken(_) => "Too much token",
+            ParseError::MismatchParen { .. } => "Paren mismatch.",
+            ParseError::NoToken { .. } => "Tokens end while parse",
+            ParseError::TooMuchToken { .. } => "Too much token",
+            ParseError::UnexpectedToken { .. } => "Unexpected token",
+            ParseError::TooDeep { .. } => "Nested too deeply",
         }
     }
 
-    fn cause(&self) -> Option<&error::Error> {
+    fn cause(&self) -> Option<&dyn error::Error> {
         None
     }
 }
@@ -1,200 +1,691 @@
 pub mod error;
+pub mod operator;
+pub mod printer;
+pub mod span;
 pub mod token;
-mod shunting_yard;
+mod parser;
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
 
-use error::CalcError;
 use error::Result;
 use expression::error::ParseError;
 use expression::error::ParseResult;
+use expression::operator::Associativity;
+use expression::operator::Fixity;
+use expression::operator::Notation;
+use expression::operator::OperatorTable;
+use expression::token::SpannedToken;
 use expression::token::Token;
-use expression::shunting_yard::shunting_yard;
+use format;
+use format::Format;
 
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Expression {
-    Number(i64),
+    Number(BigInt),
+    /// Decimal literal as written, converted by each mode on execution.
+    Decimal(String),
     Add(Box<Expression>, Box<Expression>),
     Sub(Box<Expression>, Box<Expression>),
     Mult(Box<Expression>, Box<Expression>),
     Div(Box<Expression>, Box<Expression>),
     Mod(Box<Expression>, Box<Expression>),
     Pow(Box<Expression>, Box<Expression>),
+    Neg(Box<Expression>),
+    BitAnd(Box<Expression>, Box<Expression>),
+    BitOr(Box<Expression>, Box<Expression>),
+    BitXor(Box<Expression>, Box<Expression>),
+    BitNot(Box<Expression>),
+    /// Postfix `n!`.
+    Factorial(Box<Expression>),
+    Shl(Box<Expression>, Box<Expression>),
+    /// Arithmetic shift right, `>>`.
+    Shr(Box<Expression>, Box<Expression>),
+    /// Logical shift right, `>>>`.
+    LogicalShr(Box<Expression>, Box<Expression>),
+    Eq(Box<Expression>, Box<Expression>),
+    Ne(Box<Expression>, Box<Expression>),
+    Lt(Box<Expression>, Box<Expression>),
+    Le(Box<Expression>, Box<Expression>),
+    Gt(Box<Expression>, Box<Expression>),
+    Ge(Box<Expression>, Box<Expression>),
+    /// `&&`, which evaluates the right side only when the left side is true.
+    And(Box<Expression>, Box<Expression>),
+    /// `||`, which evaluates the right side only when the left side is false.
+    Or(Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+    /// `condition ? then : otherwise`. Only the chosen branch is evaluated.
+    Conditional(Box<Expression>, Box<Expression>, Box<Expression>),
+    Variable(String),
+    Call(String, Vec<Expression>),
+    /// Operator declared at runtime, applied to its operands in order.
+    Operator(Notation, Vec<Expression>),
 }
 
+#[derive(Debug, PartialEq)]
+pub enum Statement {
+    Expression(Expression),
+    Assign(String, Expression),
+    Define(String, Vec<String>, Expression),
+    /// Declaration of an operator implemented by an expression,
+    /// such as `infix 5 left "<+>" (a, b) = (a + b) / 2`.
+    DefineOperator(Notation, Vec<String>, Expression),
+    /// Expression followed by the format of its result, such as `255 to hex`.
+    Convert(Expression, Format),
+}
+
+#[cfg(test)]
 impl Expression {
-    fn new_box_number(n: i64) -> Box<Expression> {
-        Box::new(Expression::Number(n))
+    fn new_box_number<N: Into<BigInt>>(n: N) -> Box<Expression> {
+        Box::new(Expression::Number(n.into()))
     }
     fn new_box_add(l: Box<Expression>, r: Box<Expression>) -> Box<Expression> {
         Box::new(Expression::Add(l, r))
     }
-    fn new_box_sub(l: Box<Expression>, r: Box<Expression>) -> Box<Expression> {
-        Box::new(Expression::Sub(l, r))
-    }
     fn new_box_mult(l: Box<Expression>, r: Box<Expression>) -> Box<Expression> {
         Box::new(Expression::Mult(l, r))
     }
-    fn new_box_div(l: Box<Expression>, r: Box<Expression>) -> Box<Expression> {
-        Box::new(Expression::Div(l, r))
-    }
-    fn new_box_mod(l: Box<Expression>, r: Box<Expression>) -> Box<Expression> {
-        Box::new(Expression::Mod(l, r))
-    }
     fn new_box_pow(l: Box<Expression>, r: Box<Expression>) -> Box<Expression> {
         Box::new(Expression::Pow(l, r))
     }
+    fn new_box_neg(e: Box<Expression>) -> Box<Expression> {
+        Box::new(Expression::Neg(e))
+    }
 }
 
 
+/// Parses an expression. Assignments and definitions are rejected.
 pub fn parse_expr(str: &str) -> Result<Expression> {
+    parse_expr_with(str, &OperatorTable::new())
+}
 
-    let tokens = token::parse_token(str)?;
-    let mut tokens = shunting_yard(tokens)?;
-    let expression = build_expression_tree(&mut tokens)?;
+/// Parses an expression which may use the operators declared in `operators`.
+pub fn parse_expr_with(str: &str, operators: &OperatorTable) -> Result<Expression> {
 
-    if !tokens.is_empty() {
-        let message = format!("トークンが多すぎます。");
-        return Err(CalcError::Parse(ParseError::too_much_token(message)));
+    let tokens = token::parse_token_with(str, operators)?;
+    Ok(parser::parse(tokens, str, operators)?)
+}
+
+/// Parses an expression, an assignment such as `x = 3 * 4`,
+/// a function definition such as `f(x, y) = x^2 + y`, or a conversion such as `255 to hex`.
+pub fn parse_statement(str: &str) -> Result<Statement> {
+    parse_statement_with(str, &OperatorTable::new())
+}
+
+/// Parses a statement which may use the operators declared in `operators`,
+/// or declare another one such as `infix 5 left "<+>" (a, b) = (a + b) / 2`.
+pub fn parse_statement_with(str: &str, operators: &OperatorTable) -> Result<Statement> {
+
+    if let (expr, Some(format)) = format::split_conversion(str) {
+        return Ok(Statement::Convert(parse_expr_with(expr, operators)?, format));
+    }
+
+    let mut tokens = token::parse_token_with(str, operators)?;
+
+    if let Some((notation, params, equal)) = declaration_head(&tokens, str)? {
+        // The body is split into tokens again, so that it can use the operator itself.
+        let mut operators = operators.clone();
+        operators.declare(notation.clone());
+        let equal_span = tokens[equal].span;
+        let mut tokens = token::parse_token_with(str, &operators)?;
+        let equal = match tokens.iter().position(|t| t.span == equal_span) {
+            Some(equal) if tokens[equal].token == Token::Equal => equal,
+            _ => return Err(ParseError::unexpected_token(equal_span, str).into()),
+        };
+        let body = parse_right_side(&mut tokens, equal, str, &operators)?;
+        return Ok(Statement::DefineOperator(notation, params, body));
+    }
+
+    if let Some((name, params, equal)) = definition_head(&tokens, str)? {
+        let body = parse_right_side(&mut tokens, equal, str, operators)?;
+        return Ok(Statement::Define(name, params, body));
     }
 
-    Ok(*expression)
+    let name = match tokens[..] {
+        [SpannedToken { token: Token::Ident(ref name), .. }, SpannedToken { token: Token::Equal, .. }, ..] => {
+            name.clone()
+        }
+        _ => return Ok(Statement::Expression(parser::parse(tokens, str, operators)?)),
+    };
+
+    Ok(Statement::Assign(name, parse_right_side(&mut tokens, 1, str, operators)?))
 }
 
-fn build_expression_tree(tokens: &mut Vec<Token>) -> ParseResult<Box<Expression>> {
-    if let Some(token) = tokens.pop() {
+/// Recognizes `infix 5 left "<+>" (a, b) =`, `prefix 13 "~~" (x) =` or `postfix 15 "%%" (x) =`
+/// at the head of `tokens`. The associativity of an infix operator defaults to `left`.
+/// Returns the notation, the parameters and the index of `=`.
+fn declaration_head(
+    tokens: &[SpannedToken],
+    source: &str,
+) -> ParseResult<Option<(Notation, Vec<String>, usize)>> {
+    let (fixity, precedence) = match tokens[..] {
+        [SpannedToken { token: Token::Ident(ref word), .. }, SpannedToken { token: Token::Number(ref n), span }, ..] => {
+            let fixity = match word.as_str() {
+                "infix" => Fixity::Infix,
+                "prefix" => Fixity::Prefix,
+                "postfix" => Fixity::Postfix,
+                _ => return Ok(None),
+            };
+            let precedence = n.to_u8().ok_or_else(|| ParseError::unexpected_token(span, source))?;
+            (fixity, precedence)
+        }
+        _ => return Ok(None),
+    };
 
-        match token {
-            Token::Number(n) => return Ok(Expression::new_box_number(n)),
-            Token::Plus => {
-                let right = build_expression_tree(tokens)?;
-                let left = build_expression_tree(tokens)?;
-                return Ok(Expression::new_box_add(left, right));
-            }
-            Token::Hyphen => {
-                let right = build_expression_tree(tokens)?;
-                let left = build_expression_tree(tokens)?;
-                return Ok(Expression::new_box_sub(left, right));
-            }
-            Token::Asterisk => {
-                let right = build_expression_tree(tokens)?;
-                let left = build_expression_tree(tokens)?;
-                return Ok(Expression::new_box_mult(left, right));
-            }
-            Token::Slash => {
-                let right = build_expression_tree(tokens)?;
-                let left = build_expression_tree(tokens)?;
-                return Ok(Expression::new_box_div(left, right));
-            }
-            Token::Percent => {
-                let right = build_expression_tree(tokens)?;
-                let left = build_expression_tree(tokens)?;
-                return Ok(Expression::new_box_mod(left, right));
+    let mut i = 2;
+    let associativity = match tokens.get(i).map(|t| &t.token) {
+        Some(Token::Ident(word)) if fixity == Fixity::Infix && word == "right" => {
+            i += 1;
+            Associativity::Right
+        }
+        Some(Token::Ident(word)) if fixity == Fixity::Infix && word == "left" => {
+            i += 1;
+            Associativity::Left
+        }
+        _ => Associativity::Left,
+    };
+
+    let notation = match tokens.get(i) {
+        Some(&SpannedToken { token: Token::Str(ref symbol), span }) => {
+            let notation = match fixity {
+                Fixity::Infix => Notation::infix(symbol, precedence, associativity),
+                Fixity::Prefix => Notation::prefix(symbol, precedence),
+                Fixity::Postfix => Notation::postfix(symbol, precedence),
+            };
+            notation.map_err(|_| ParseError::unexpected_token(span, source))?
+        }
+        _ => return Err(unexpected(tokens, i, source)),
+    };
+
+    let (params, i) = match parameters(tokens, i + 1, source)? {
+        Some((params, _)) if params.len() != notation.arity() => {
+            return Err(ParseError::unexpected_token(tokens[i + 1].span, source));
+        }
+        Some(found) => found,
+        None => return Err(unexpected(tokens, i + 1, source)),
+    };
+
+    match tokens.get(i) {
+        Some(&SpannedToken { token: Token::Equal, .. }) => Ok(Some((notation, params, i))),
+        _ => Err(unexpected(tokens, i, source)),
+    }
+}
+
+/// Error for the token at `i`, or for the end of the tokens after it.
+fn unexpected(tokens: &[SpannedToken], i: usize, source: &str) -> ParseError {
+    match tokens.get(i) {
+        Some(t) => ParseError::unexpected_token(t.span, source),
+        None => ParseError::no_token(tokens[i - 1].span, source),
+    }
+}
+
+/// Recognizes `name(param, ...) =` at the head of `tokens`.
+/// Returns the name, the parameters and the index of `=`.
+fn definition_head(
+    tokens: &[SpannedToken],
+    source: &str,
+) -> ParseResult<Option<(String, Vec<String>, usize)>> {
+    let name = match tokens[..] {
+        [SpannedToken { token: Token::Ident(ref name), .. }, SpannedToken { token: Token::OpenParen, .. }, ..] => {
+            name.clone()
+        }
+        _ => return Ok(None),
+    };
+
+    let (params, i) = match parameters(tokens, 1, source)? {
+        Some(found) => found,
+        None => return Ok(None),
+    };
+
+    match tokens.get(i) {
+        Some(&SpannedToken { token: Token::Equal, .. }) => Ok(Some((name, params, i))),
+        _ => Ok(None),
+    }
+}
+
+/// Recognizes `(param, ...)` from the `(` at `open`.
+/// Returns the names and the index after `)`. Duplicate names are an error.
+fn parameters(
+    tokens: &[SpannedToken],
+    open: usize,
+    source: &str,
+) -> ParseResult<Option<(Vec<String>, usize)>> {
+    match tokens.get(open) {
+        Some(&SpannedToken { token: Token::OpenParen, .. }) => (),
+        _ => return Ok(None),
+    }
+
+    let mut params = Vec::<&SpannedToken>::new();
+    let mut i = open + 1;
+    loop {
+        match (tokens.get(i).map(|t| &t.token), tokens.get(i + 1).map(|t| &t.token)) {
+            (Some(&Token::Ident(_)), Some(&Token::Comma)) => params.push(&tokens[i]),
+            (Some(&Token::Ident(_)), Some(&Token::CloseParen)) => {
+                params.push(&tokens[i]);
+                i += 2;
+                break;
             }
-            Token::Hat => {
-                let right = build_expression_tree(tokens)?;
-                let left = build_expression_tree(tokens)?;
-                return Ok(Expression::new_box_pow(left, right));
+            (Some(&Token::CloseParen), _) if params.is_empty() => {
+                i += 1;
+                break;
             }
-            Token::OpenParen => panic!("build_expression_tree(): invalid token 'OpenParen'"),
-            Token::CloseParen => panic!("build_expression_tree(): invalid token 'CloseParen'"),
+            _ => return Ok(None),
         }
+        i += 2;
+    }
 
-    } else {
-        return Err(ParseError::no_token(
-            "式の途中でトークンが無くなりました。",
-        ));
+    let mut names = Vec::<String>::with_capacity(params.len());
+    for param in params {
+        if let Token::Ident(ref param_name) = param.token {
+            if names.contains(param_name) {
+                return Err(ParseError::unexpected_token(param.span, source));
+            }
+            names.push(param_name.clone());
+        }
     }
+
+    Ok(Some((names, i)))
 }
 
+/// Parses the tokens after the `=` at `equal`.
+fn parse_right_side(
+    tokens: &mut Vec<SpannedToken>,
+    equal: usize,
+    source: &str,
+    operators: &OperatorTable,
+) -> ParseResult<Expression> {
+    let right = tokens.split_off(equal + 1);
+    if right.is_empty() {
+        return Err(ParseError::no_token(tokens[equal].span, source));
+    }
+
+    parser::parse(right, source, operators)
+}
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use error::CalcError;
     use expression::error::ParseError;
+    use expression::span::Span;
+    use expression::operator::Associativity;
 
     //// parse_expr ////
 
+    #[test]
+    fn parse_expr_neg_pow() {
+        let result = parse_expr("-2^2");
 
+        let expr = result.expect("Test returns Err().");
+        assert_eq!(
+            expr,
+            *Expression::new_box_neg(Expression::new_box_pow(
+                Expression::new_box_number(2),
+                Expression::new_box_number(2),
+            ))
+        );
+    }
 
-    //// build_expression_tree ////
+    #[test]
+    fn parse_expr_mult_neg() {
+        let result = parse_expr("3 * -2");
+
+        let expr = result.expect("Test returns Err().");
+        assert_eq!(
+            expr,
+            *Expression::new_box_mult(
+                Expression::new_box_number(3),
+                Expression::new_box_number(-2),
+            )
+        );
+    }
 
     #[test]
-    fn build_expression_tree_add() {
-        let mut tokens = vec![Token::Number(1), Token::Number(2), Token::Plus];
+    fn parse_expr_pow_neg() {
+        let result = parse_expr("2^-1");
 
-        let result = build_expression_tree(&mut tokens);
+        let expr = result.expect("Test returns Err().");
+        assert_eq!(
+            expr,
+            *Expression::new_box_pow(
+                Expression::new_box_number(2),
+                Expression::new_box_number(-1),
+            )
+        );
+    }
+
+    #[test]
+    fn parse_expr_double_neg() {
+        let result = parse_expr("--1");
 
         let expr = result.expect("Test returns Err().");
         assert_eq!(
             expr,
-            Expression::new_box_add(Expression::new_box_number(1), Expression::new_box_number(2))
+            *Expression::new_box_neg(Expression::new_box_number(-1))
         );
     }
 
     #[test]
-    fn build_expression_tree_add_mult_pow() {
-        let mut tokens = vec![
-            Token::Number(1),
-            Token::Number(2),
-            Token::Plus,
-            Token::Number(3),
-            Token::Asterisk,
-            Token::Number(4),
-            Token::Hat,
-        ];
+    fn parse_expr_neg_number_min() {
+        let result = parse_expr("-9223372036854775808");
+
+        let expr = result.expect("Test returns Err().");
+        assert_eq!(expr, Expression::Number(i64::MIN.into()));
+    }
+
+    #[test]
+    fn parse_expr_neg_paren() {
+        let result = parse_expr("-(2)");
 
-        let result = build_expression_tree(&mut tokens);
+        let expr = result.expect("Test returns Err().");
+        assert_eq!(expr, *Expression::new_box_neg(Expression::new_box_number(2)));
+    }
+
+    #[test]
+    fn parse_expr_unary_plus() {
+        let result = parse_expr("+(1)");
+
+        let expr = result.expect("Test returns Err().");
+        assert_eq!(expr, *Expression::new_box_number(1));
+    }
+
+    #[test]
+    fn parse_expr_too_much_token() {
+        let result = parse_expr("(1)(2)");
+
+        match result.expect_err("Test should returns Err().") {
+            CalcError::Parse(ref err @ ParseError::TooMuchToken { .. }) => {
+                let message = err.to_string();
+                assert!(message.ends_with("入力: \"(1)(2)\"\n          ^ 余分なトークン\n"))
+            }
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_expr_empty() {
+        let result = parse_expr("");
+
+        match result.expect_err("Test should returns Err().") {
+            CalcError::Parse(ParseError::NoToken { .. }) => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_expr_variable() {
+        let result = parse_expr("x * 2");
 
         let expr = result.expect("Test returns Err().");
         assert_eq!(
             expr,
-            Expression::new_box_pow(
-                Expression::new_box_mult(
-                    Expression::new_box_add(Expression::new_box_number(1), Expression::new_box_number(2)),
-                    Expression::new_box_number(3),
-                ),
-                Expression::new_box_number(4),
+            *Expression::new_box_mult(
+                Box::new(Expression::Variable("x".to_string())),
+                Expression::new_box_number(2),
             )
         );
     }
 
     #[test]
-    fn build_expression_tree_sub_div_mod() {
-        let mut tokens = vec![
-            Token::Number(1),
-            Token::Number(2),
-            Token::Hyphen,
-            Token::Number(3),
-            Token::Slash,
-            Token::Number(4),
-            Token::Percent,
-        ];
+    fn parse_expr_call() {
+        let result = parse_expr("log(x, 2) + 1");
+
+        let expr = result.expect("Test returns Err().");
+        assert_eq!(
+            expr,
+            *Expression::new_box_add(
+                Box::new(Expression::Call(
+                    "log".to_string(),
+                    vec![Expression::Variable("x".to_string()), Expression::Number(2.into())],
+                )),
+                Expression::new_box_number(1),
+            )
+        );
+    }
+
+    #[test]
+    fn parse_expr_call_neg_argument() {
+        let result = parse_expr("abs(-3)");
 
-        let result = build_expression_tree(&mut tokens);
+        let expr = result.expect("Test returns Err().");
+        let expected = Expression::Call("abs".to_string(), vec![Expression::Number((-3).into())]);
+        assert_eq!(expr, expected);
+    }
+
+    #[test]
+    fn parse_expr_conditional_precedence() {
+        let result = parse_expr("x < 1 || !y ? 2 : x == 3");
 
+        let var = |name: &str| Box::new(Expression::Variable(name.to_string()));
         let expr = result.expect("Test returns Err().");
         assert_eq!(
             expr,
-            Expression::new_box_mod(
-                Expression::new_box_div(
-                    Expression::new_box_sub(Expression::new_box_number(1), Expression::new_box_number(2)),
+            Expression::Conditional(
+                Box::new(Expression::Or(
+                    Box::new(Expression::Lt(var("x"), Expression::new_box_number(1))),
+                    Box::new(Expression::Not(var("y"))),
+                )),
+                Expression::new_box_number(2),
+                Box::new(Expression::Eq(var("x"), Expression::new_box_number(3))),
+            )
+        );
+    }
+
+    #[test]
+    fn parse_expr_question_without_colon() {
+        let result = parse_expr("(x ? 1) + 2");
+
+        match result.expect_err("Test should returns Err().") {
+            CalcError::Parse(ref err @ ParseError::UnexpectedToken { .. }) => {
+                assert_eq!(err.span(), Span::new(3, 4))
+            }
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_expr_misplaced_equal() {
+        let result = parse_expr("1 + x = 2");
+
+        match result.expect_err("Test should returns Err().") {
+            CalcError::Parse(ParseError::UnexpectedToken { .. }) => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    //// parse_statement ////
+
+    #[test]
+    fn parse_statement_expression() {
+        let result = parse_statement("1 + 2");
+
+        let stmt = result.expect("Test returns Err().");
+        assert_eq!(
+            stmt,
+            Statement::Expression(*Expression::new_box_add(
+                Expression::new_box_number(1),
+                Expression::new_box_number(2),
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_statement_convert() {
+        let result = parse_statement("1 + 2 to sci 3");
+
+        let stmt = result.expect("Test returns Err().");
+        assert_eq!(
+            stmt,
+            Statement::Convert(
+                *Expression::new_box_add(
+                    Expression::new_box_number(1),
+                    Expression::new_box_number(2),
+                ),
+                Format::Scientific(3),
+            )
+        );
+    }
+
+    #[test]
+    fn parse_statement_assign() {
+        let result = parse_statement("x = 3 * 4");
+
+        let stmt = result.expect("Test returns Err().");
+        assert_eq!(
+            stmt,
+            Statement::Assign(
+                "x".to_string(),
+                *Expression::new_box_mult(
                     Expression::new_box_number(3),
+                    Expression::new_box_number(4),
                 ),
-                Expression::new_box_number(4),
             )
         );
     }
 
     #[test]
-    fn build_expression_tree_fail_no_number() {
-        let mut tokens = vec![Token::Number(1), Token::Plus];
+    fn parse_statement_assign_without_value() {
+        let result = parse_statement("x =");
 
-        let result = build_expression_tree(&mut tokens);
+        match result.expect_err("Test should returns Err().") {
+            CalcError::Parse(ref err @ ParseError::NoToken { .. }) => {
+                let message = err.to_string();
+                assert!(message.ends_with("入力: \"x =\"\n         ^ 被演算子がありません\n"))
+            }
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
 
-        let expr = result.expect_err("Test should returns Err().");
-        match expr {
-            ParseError::NoToken(_) => return,
+    #[test]
+    fn parse_statement_double_assign() {
+        let result = parse_statement("x = y = 1");
+
+        match result.expect_err("Test should returns Err().") {
+            CalcError::Parse(ParseError::UnexpectedToken { .. }) => (),
             e => panic!("Unexcepted error: {:?}", e),
         }
     }
+
+    #[test]
+    fn parse_statement_define() {
+        let result = parse_statement("f(x, y) = x ^ 2 + y");
+
+        let stmt = result.expect("Test returns Err().");
+        assert_eq!(
+            stmt,
+            Statement::Define(
+                "f".to_string(),
+                vec!["x".to_string(), "y".to_string()],
+                *Expression::new_box_add(
+                    Expression::new_box_pow(
+                        Box::new(Expression::Variable("x".to_string())),
+                        Expression::new_box_number(2),
+                    ),
+                    Box::new(Expression::Variable("y".to_string())),
+                ),
+            )
+        );
+    }
+
+    #[test]
+    fn parse_statement_define_without_params() {
+        let result = parse_statement("two() = 2");
+
+        let stmt = result.expect("Test returns Err().");
+        assert_eq!(
+            stmt,
+            Statement::Define("two".to_string(), vec![], *Expression::new_box_number(2))
+        );
+    }
+
+    #[test]
+    fn parse_statement_call_is_not_definition() {
+        let result = parse_statement("f(x, 2)");
+
+        let stmt = result.expect("Test returns Err().");
+        assert_eq!(
+            stmt,
+            Statement::Expression(Expression::Call(
+                "f".to_string(),
+                vec![Expression::Variable("x".to_string()), Expression::Number(2.into())],
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_statement_call_neg_argument() {
+        let result = parse_statement("f(-3)");
+
+        let stmt = result.expect("Test returns Err().");
+        assert_eq!(
+            stmt,
+            Statement::Expression(Expression::Call(
+                "f".to_string(),
+                vec![Expression::Number((-3).into())],
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_statement_define_duplicate_param() {
+        let result = parse_statement("f(x, x) = x");
+
+        match result.expect_err("Test should returns Err().") {
+            CalcError::Parse(ref err @ ParseError::UnexpectedToken { .. }) => {
+                let message = err.to_string();
+                assert!(message.ends_with("入力: \"f(x, x) = x\"\n            ^ 不正なトークン\n"))
+            }
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_statement_define_operator() {
+        let result = parse_statement("infix 5 \"<+>\" (a, b) = a <+> b - 1");
+
+        let notation = Notation::infix("<+>", 5, Associativity::Left).expect("Test returns Err().");
+        let var = |name: &str| Expression::Variable(name.to_string());
+        let right = Expression::Sub(Box::new(var("b")), Expression::new_box_number(1));
+        let stmt = result.expect("Test returns Err().");
+        assert_eq!(
+            stmt,
+            Statement::DefineOperator(
+                notation.clone(),
+                vec!["a".to_string(), "b".to_string()],
+                Expression::Operator(
+                    notation,
+                    vec![var("a"), right],
+                ),
+            )
+        );
+    }
+
+    #[test]
+    fn parse_statement_define_operator_invalid() {
+        let cases = [
+            ("infix 300 \"<+>\" (a, b) = a", Span::new(6, 9)),
+            ("infix 5 \"+\" (a, b) = a", Span::new(8, 11)),
+            ("infix 5 up \"<+>\" (a, b) = a", Span::new(8, 10)),
+            ("prefix 13 \"<+>\" (a, b) = a", Span::new(16, 17)),
+            ("postfix 15 \"<+>\" (a) a", Span::new(21, 22)),
+        ];
+
+        for &(source, span) in &cases {
+            match parse_statement(source).expect_err("Test should returns Err().") {
+                CalcError::Parse(ref err @ ParseError::UnexpectedToken { .. }) => {
+                    assert_eq!(err.span(), span, "{}", source)
+                }
+                e => panic!("Unexcepted error: {:?}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_statement_keyword_as_variable() {
+        let result = parse_statement("infix = 5");
+
+        let stmt = result.expect("Test returns Err().");
+        assert_eq!(stmt, Statement::Assign("infix".to_string(), *Expression::new_box_number(5)));
+    }
 }
@@ -0,0 +1,435 @@
+use std::fmt;
+
+use expression::Expression;
+use expression::error::ParseError;
+use expression::error::ParseResult;
+use expression::span::Span;
+use expression::token::Token;
+
+
+/// Position of an operator relative to its operands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Fixity {
+    Prefix,
+    Infix,
+    Postfix,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+/// Constructor of the expression an operator stands for. The variant decides the arity.
+pub enum Build {
+    Unary(fn(Box<Expression>) -> Expression),
+    Binary(fn(Box<Expression>, Box<Expression>) -> Expression),
+    /// Operator whose last two operands are separated by another symbol and token,
+    /// as `:` in `c ? a : b`.
+    Ternary(
+        &'static str,
+        Token,
+        fn(Box<Expression>, Box<Expression>, Box<Expression>) -> Expression,
+    ),
+}
+
+/// Entry of the operator table, which is all the parser knows about an operator.
+pub struct Operator {
+    pub symbol: &'static str,
+    pub token: Token,
+    pub fixity: Fixity,
+    /// Binding strength. Operators of higher precedence take their operands first.
+    pub precedence: u8,
+    pub associativity: Associativity,
+    pub build: Build,
+}
+
+impl Operator {
+    /// Number of operands.
+    pub fn arity(&self) -> usize {
+        match self.build {
+            Build::Unary(_) => 1,
+            Build::Binary(_) => 2,
+            Build::Ternary(..) => 3,
+        }
+    }
+}
+
+/// Every operator. Bitwise, comparison and logical operators bind as in C,
+/// and the factorial binds tighter than anything else.
+static OPERATORS: &[Operator] = &[
+    Operator {
+        symbol: "?",
+        token: Token::Question,
+        fixity: Fixity::Infix,
+        precedence: 1,
+        associativity: Associativity::Right,
+        build: Build::Ternary(":", Token::Colon, Expression::Conditional),
+    },
+    infix("||", Token::DoublePipe, 2, Expression::Or),
+    infix("&&", Token::DoubleAmpersand, 3, Expression::And),
+    infix("|", Token::Pipe, 4, Expression::BitOr),
+    infix("xor", Token::Xor, 5, Expression::BitXor),
+    infix("&", Token::Ampersand, 6, Expression::BitAnd),
+    infix("==", Token::DoubleEqual, 7, Expression::Eq),
+    infix("!=", Token::NotEqual, 7, Expression::Ne),
+    infix("<", Token::Less, 8, Expression::Lt),
+    infix("<=", Token::LessEqual, 8, Expression::Le),
+    infix(">", Token::Greater, 8, Expression::Gt),
+    infix(">=", Token::GreaterEqual, 8, Expression::Ge),
+    infix("<<", Token::ShiftLeft, 9, Expression::Shl),
+    infix(">>", Token::ShiftRight, 9, Expression::Shr),
+    infix(">>>", Token::LogicalShiftRight, 9, Expression::LogicalShr),
+    infix("+", Token::Plus, 10, Expression::Add),
+    infix("-", Token::Hyphen, 10, Expression::Sub),
+    infix("%", Token::Percent, 11, Expression::Mod),
+    infix("*", Token::Asterisk, 12, Expression::Mult),
+    infix("/", Token::Slash, 12, Expression::Div),
+    prefix("+", Token::Plus, 13, unary_plus),
+    prefix("-", Token::Hyphen, 13, Expression::Neg),
+    prefix("~", Token::Tilde, 13, Expression::BitNot),
+    prefix("!", Token::Bang, 13, Expression::Not),
+    Operator {
+        symbol: "^",
+        token: Token::Hat,
+        fixity: Fixity::Infix,
+        precedence: 14,
+        associativity: Associativity::Right,
+        build: Build::Binary(Expression::Pow),
+    },
+    Operator {
+        symbol: "!",
+        token: Token::Bang,
+        fixity: Fixity::Postfix,
+        precedence: 15,
+        associativity: Associativity::Left,
+        build: Build::Unary(Expression::Factorial),
+    },
+];
+
+/// Looks up a built-in operator by its symbol and position.
+pub fn builtin(symbol: &str, fixity: Fixity) -> Option<&'static Operator> {
+    OPERATORS.iter().find(|op| op.symbol == symbol && op.fixity == fixity)
+}
+
+/// Left-associative binary operator.
+const fn infix(
+    symbol: &'static str,
+    token: Token,
+    precedence: u8,
+    build: fn(Box<Expression>, Box<Expression>) -> Expression,
+) -> Operator {
+    Operator {
+        symbol,
+        token,
+        fixity: Fixity::Infix,
+        precedence,
+        associativity: Associativity::Left,
+        build: Build::Binary(build),
+    }
+}
+
+const fn prefix(
+    symbol: &'static str,
+    token: Token,
+    precedence: u8,
+    build: fn(Box<Expression>) -> Expression,
+) -> Operator {
+    Operator {
+        symbol,
+        token,
+        fixity: Fixity::Prefix,
+        precedence,
+        associativity: Associativity::Right,
+        build: Build::Unary(build),
+    }
+}
+
+// The box comes with the signature of `Build::Unary`.
+#[allow(clippy::boxed_local)]
+fn unary_plus(operand: Box<Expression>) -> Expression {
+    *operand
+}
+
+/// Words that cannot be declared as operators.
+const KEYWORDS: &[&str] = &["xor", "infix", "prefix", "postfix", "left", "right"];
+
+/// How an operator declared at runtime is written, such as `infix 5 left "<+>"`.
+/// The symbol is checked on construction, so a `Notation` can always be tokenized.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Notation {
+    symbol: String,
+    fixity: Fixity,
+    precedence: u8,
+    associativity: Associativity,
+}
+
+impl Notation {
+    /// Binary operator between its operands.
+    pub fn infix(
+        symbol: &str,
+        precedence: u8,
+        associativity: Associativity,
+    ) -> ParseResult<Notation> {
+        Notation::new(symbol, Fixity::Infix, precedence, associativity)
+    }
+
+    /// Unary operator before its operand.
+    pub fn prefix(symbol: &str, precedence: u8) -> ParseResult<Notation> {
+        Notation::new(symbol, Fixity::Prefix, precedence, Associativity::Right)
+    }
+
+    /// Unary operator after its operand.
+    pub fn postfix(symbol: &str, precedence: u8) -> ParseResult<Notation> {
+        Notation::new(symbol, Fixity::Postfix, precedence, Associativity::Left)
+    }
+
+    /// A symbol is either a word that is not a keyword, or punctuation other than parentheses,
+    /// commas, quotes and underscores that is not a built-in operator.
+    fn new(
+        symbol: &str,
+        fixity: Fixity,
+        precedence: u8,
+        associativity: Associativity,
+    ) -> ParseResult<Notation> {
+        let is_word = symbol.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && symbol.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && !KEYWORDS.contains(&symbol);
+        let is_punctuation = !symbol.is_empty()
+            && symbol.chars().all(|c| !c.is_alphanumeric() && !c.is_whitespace()
+                && !"(),\"_".contains(c))
+            && !OPERATORS.iter().any(|op| op.symbol == symbol || separator(op) == Some(symbol))
+            && !["=", ","].contains(&symbol);
+        if !is_word && !is_punctuation {
+            return Err(ParseError::unexpected_token(Span::new(0, symbol.len()), symbol));
+        }
+
+        Ok(Notation { symbol: symbol.to_string(), fixity, precedence, associativity })
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    pub fn fixity(&self) -> Fixity {
+        self.fixity
+    }
+
+    pub fn precedence(&self) -> u8 {
+        self.precedence
+    }
+
+    pub fn associativity(&self) -> Associativity {
+        self.associativity
+    }
+
+    /// Number of operands.
+    pub fn arity(&self) -> usize {
+        match self.fixity {
+            Fixity::Infix => 2,
+            Fixity::Prefix | Fixity::Postfix => 1,
+        }
+    }
+}
+
+/// Renders the head of a declaration, such as `infix 5 left "<+>"`.
+impl fmt::Display for Notation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.fixity, self.associativity) {
+            (Fixity::Infix, Associativity::Left) => write!(f, "infix {} left", self.precedence)?,
+            (Fixity::Infix, Associativity::Right) => write!(f, "infix {} right", self.precedence)?,
+            (Fixity::Prefix, _) => write!(f, "prefix {}", self.precedence)?,
+            (Fixity::Postfix, _) => write!(f, "postfix {}", self.precedence)?,
+        }
+        write!(f, " \"{}\"", self.symbol)
+    }
+}
+
+fn separator(op: &Operator) -> Option<&'static str> {
+    match op.build {
+        Build::Ternary(symbol, _, _) => Some(symbol),
+        _ => None,
+    }
+}
+
+/// Operator found in a table.
+#[derive(Clone, Copy)]
+pub enum Entry<'a> {
+    Builtin(&'static Operator),
+    Custom(&'a Notation),
+}
+
+impl<'a> Entry<'a> {
+    pub fn precedence(&self) -> u8 {
+        match *self {
+            Entry::Builtin(op) => op.precedence,
+            Entry::Custom(notation) => notation.precedence,
+        }
+    }
+
+    pub fn associativity(&self) -> Associativity {
+        match *self {
+            Entry::Builtin(op) => op.associativity,
+            Entry::Custom(notation) => notation.associativity,
+        }
+    }
+}
+
+/// Built-in operators and the ones declared at runtime.
+#[derive(Clone, Debug, Default)]
+pub struct OperatorTable {
+    custom: Vec<Notation>,
+}
+
+impl OperatorTable {
+    pub fn new() -> OperatorTable {
+        OperatorTable::default()
+    }
+
+    /// Adds an operator. It replaces the one with the same symbol on the same side of an operand,
+    /// i.e. a prefix operator replaces a prefix one, and others replace infix and postfix ones.
+    pub fn declare(&mut self, notation: Notation) {
+        let is_prefix = notation.fixity == Fixity::Prefix;
+        self.custom.retain(|other| {
+            other.symbol != notation.symbol || (other.fixity == Fixity::Prefix) != is_prefix
+        });
+        self.custom.push(notation);
+    }
+
+    /// Operators declared at runtime, in declaration order.
+    pub fn custom(&self) -> &[Notation] {
+        &self.custom
+    }
+
+    /// Whether `word` is declared as an operator.
+    pub fn is_symbol(&self, word: &str) -> bool {
+        self.custom.iter().any(|notation| notation.symbol == word)
+    }
+
+    /// Longest declared symbol at the head of `s`.
+    pub fn symbol_at(&self, s: &str) -> Option<&str> {
+        self.custom
+            .iter()
+            .map(|notation| notation.symbol.as_str())
+            .filter(|&symbol| s.starts_with(symbol))
+            .max_by_key(|symbol| symbol.len())
+    }
+
+    /// Looks up the operator `token` stands for before an operand.
+    pub fn find_prefix(&self, token: &Token) -> Option<Entry<'_>> {
+        self.find(token, |fixity| fixity == Fixity::Prefix)
+    }
+
+    /// Looks up the operator `token` stands for after an operand, which is infix or postfix.
+    pub fn find_after_operand(&self, token: &Token) -> Option<Entry<'_>> {
+        self.find(token, |fixity| fixity != Fixity::Prefix)
+    }
+
+    fn find<F: Fn(Fixity) -> bool>(&self, token: &Token, position: F) -> Option<Entry<'_>> {
+        match *token {
+            Token::Operator(ref symbol) => self
+                .custom
+                .iter()
+                .find(|notation| notation.symbol == *symbol && position(notation.fixity))
+                .map(Entry::Custom),
+            ref token => OPERATORS
+                .iter()
+                .find(|op| op.token == *token && position(op.fixity))
+                .map(Entry::Builtin),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    //// OPERATORS ////
+
+    #[test]
+    fn operators_arity_matches_fixity() {
+        for op in OPERATORS {
+            let arities: &[usize] = match op.fixity {
+                Fixity::Prefix | Fixity::Postfix => &[1],
+                Fixity::Infix => &[2, 3],
+            };
+            assert!(arities.contains(&op.arity()), "{}", op.symbol);
+        }
+    }
+
+    #[test]
+    fn operators_are_unambiguous() {
+        for (i, op) in OPERATORS.iter().enumerate() {
+            let before_operand = op.fixity == Fixity::Prefix;
+            assert!(
+                OPERATORS[i + 1..].iter().all(|other| other.token != op.token
+                    || (other.fixity == Fixity::Prefix) != before_operand),
+                "{}",
+                op.symbol
+            );
+        }
+    }
+
+    //// Notation ////
+
+    #[test]
+    fn notation_symbols() {
+        assert!(Notation::infix("<+>", 5, Associativity::Left).is_ok());
+        assert!(Notation::prefix("√", 13).is_ok());
+        assert!(Notation::postfix("percent", 15).is_ok());
+
+        for symbol in &["", "+", ":", "=", "a b", "(+)", "<,>", "\"", "2x", "xor", "left"] {
+            match Notation::infix(symbol, 5, Associativity::Left) {
+                Err(ParseError::UnexpectedToken { .. }) => (),
+                result => panic!("Unexcepted result for {:?}: {:?}", symbol, result),
+            }
+        }
+    }
+
+    #[test]
+    fn notation_display() {
+        let infix = Notation::infix("<+>", 5, Associativity::Right).expect("Test returns Err().");
+        let postfix = Notation::postfix("%%", 15).expect("Test returns Err().");
+
+        assert_eq!(infix.to_string(), "infix 5 right \"<+>\"");
+        assert_eq!(postfix.to_string(), "postfix 15 \"%%\"");
+    }
+
+    //// OperatorTable ////
+
+    #[test]
+    fn operator_table_find_by_position() {
+        let table = OperatorTable::new();
+
+        match (table.find_prefix(&Token::Bang), table.find_after_operand(&Token::Bang)) {
+            (Some(Entry::Builtin(not)), Some(Entry::Builtin(factorial))) => {
+                assert_eq!((not.fixity, not.precedence), (Fixity::Prefix, 13));
+                assert_eq!((factorial.fixity, factorial.precedence), (Fixity::Postfix, 15));
+            }
+            _ => panic!("Unexcepted entries."),
+        }
+        assert!(table.find_prefix(&Token::Asterisk).is_none());
+        assert!(table.find_after_operand(&Token::Tilde).is_none());
+    }
+
+    #[test]
+    fn operator_table_declare_replaces() {
+        let mut table = OperatorTable::new();
+
+        table.declare(Notation::infix("<>", 5, Associativity::Left).expect("Test returns Err()."));
+        table.declare(Notation::prefix("<>", 13).expect("Test returns Err()."));
+        table.declare(Notation::postfix("<>", 15).expect("Test returns Err()."));
+        table.declare(Notation::infix("<>>", 9, Associativity::Left).expect("Test returns Err()."));
+
+        let token = Token::Operator("<>".to_string());
+        let after = table.find_after_operand(&token).map(|entry| entry.precedence());
+        assert_eq!(after, Some(15));
+        assert_eq!(table.find_prefix(&token).map(|entry| entry.precedence()), Some(13));
+        assert_eq!(table.custom().len(), 3);
+        assert_eq!(table.symbol_at("<>> 1"), Some("<>>"));
+        assert_eq!(table.symbol_at("<> 1"), Some("<>"));
+        assert_eq!(table.symbol_at("< 1"), None);
+    }
+}
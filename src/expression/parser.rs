@@ -0,0 +1,557 @@
+use expression::Expression;
+use expression::error::ParseError;
+use expression::error::ParseResult;
+use expression::operator::Associativity;
+use expression::operator::Build;
+use expression::operator::Entry;
+use expression::operator::Fixity;
+use expression::operator::Operator;
+use expression::operator::OperatorTable;
+use expression::span::Span;
+use expression::token::SpannedToken;
+use expression::token::Token;
+
+
+/// Upper bound of the nesting of parentheses, prefix operators, right operands and arguments
+/// in an expression. Deeper expressions are rejected before parsing them exhausts the stack.
+/// Left-associative chains such as `1 + 2 + 3` are built in a loop and do not nest.
+pub const MAX_NESTING: usize = 256;
+
+/// Precedence climbing parser driven by the operator table.
+struct Parser<'a> {
+    /// Tokens not consumed yet, in reverse order.
+    tokens: Vec<SpannedToken>,
+    /// Span of the last consumed token, reported when an operand is missing after it.
+    last_span: Span,
+    source: &'a str,
+    operators: &'a OperatorTable,
+}
+
+/// Parses the whole of `tokens` as an expression.
+pub fn parse(
+    mut tokens: Vec<SpannedToken>,
+    source: &str,
+    operators: &OperatorTable,
+) -> ParseResult<Expression> {
+    tokens.reverse();
+    let mut parser = Parser { tokens, last_span: Span::new(0, 0), source, operators };
+
+    let expression = parser.expression(0, 0)?;
+    match parser.next() {
+        None => Ok(*expression),
+        Some(t) => Err(parser.stray(t)),
+    }
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Option<SpannedToken> {
+        let t = self.tokens.pop();
+        if let Some(ref t) = t {
+            self.last_span = t.span;
+        }
+        t
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.last().map(|t| &t.token)
+    }
+
+    /// Parses operators of at least `min_precedence` and their operands.
+    /// `nesting` counts the parentheses and operators around the expression.
+    fn expression(
+        &mut self,
+        min_precedence: u16,
+        nesting: usize,
+    ) -> ParseResult<Box<Expression>> {
+        let mut left = self.operand(nesting)?;
+
+        loop {
+            let operators = self.operators;
+            let entry = match self.peek().and_then(|token| operators.find_after_operand(token)) {
+                Some(entry) if u16::from(entry.precedence()) >= min_precedence => entry,
+                _ => return Ok(left),
+            };
+            let span = self.next().map_or(self.last_span, |t| t.span);
+            let right_precedence = right_precedence(entry);
+
+            left = match entry {
+                Entry::Builtin(&Operator { build: Build::Unary(build), .. }) => {
+                    Box::new(build(left))
+                }
+                Entry::Builtin(&Operator { build: Build::Binary(build), .. }) => {
+                    let right = self.expression(right_precedence, nesting + 1)?;
+                    Box::new(build(left, right))
+                }
+                Entry::Builtin(&Operator { build: Build::Ternary(_, ref sep, build), .. }) => {
+                    let middle = self.expression(0, nesting + 1)?;
+                    match self.next() {
+                        Some(ref t) if t.token == *sep => (),
+                        Some(t) if !matches!(t.token, Token::CloseParen | Token::Comma) => {
+                            return Err(self.stray(t));
+                        }
+                        _ => return Err(ParseError::unexpected_token(span, self.source)),
+                    }
+                    let right = self.expression(right_precedence, nesting + 1)?;
+                    Box::new(build(left, middle, right))
+                }
+                Entry::Custom(notation) if notation.fixity() == Fixity::Infix => {
+                    let right = self.expression(right_precedence, nesting + 1)?;
+                    Box::new(Expression::Operator(notation.clone(), vec![*left, *right]))
+                }
+                Entry::Custom(notation) => {
+                    Box::new(Expression::Operator(notation.clone(), vec![*left]))
+                }
+            };
+        }
+    }
+
+    /// Parses a literal, a variable, a call, a parenthesized expression,
+    /// or a prefix operator and its operand.
+    fn operand(&mut self, nesting: usize) -> ParseResult<Box<Expression>> {
+        let before = self.last_span;
+        let SpannedToken { token, span } = match self.next() {
+            Some(t) => t,
+            None => return Err(ParseError::no_token(before, self.source)),
+        };
+        if nesting > MAX_NESTING {
+            return Err(ParseError::too_deep(span, self.source));
+        }
+
+        match token {
+            Token::Number(n) => Ok(Box::new(Expression::Number(n))),
+            Token::Decimal(s) => Ok(Box::new(Expression::Decimal(s))),
+            Token::Ident(name) => match self.peek() {
+                // An identifier directly followed by `(` is a function name.
+                Some(&Token::OpenParen) => self.call(name, nesting),
+                _ => Ok(Box::new(Expression::Variable(name))),
+            },
+            Token::OpenParen => {
+                let inner = self.expression(0, nesting + 1)?;
+                self.close_paren(span)?;
+                Ok(inner)
+            }
+            Token::Equal => Err(ParseError::unexpected_token(span, self.source)),
+            Token::Comma | Token::CloseParen => Err(ParseError::no_token(before, self.source)),
+            Token::Str(_) => Err(ParseError::unexpected_token(span, self.source)),
+            token => match self.operators.find_prefix(&token) {
+                Some(Entry::Builtin(&Operator {
+                    symbol, precedence, build: Build::Unary(build), ..
+                })) => {
+                    let literal = symbol == "-" && matches!(self.peek(), Some(&Token::Number(_)));
+                    let operand = self.expression(u16::from(precedence), nesting + 1)?;
+                    match *operand {
+                        // A minus sign directly before a number makes a negative literal.
+                        Expression::Number(n) if literal => Ok(Box::new(Expression::Number(-n))),
+                        expression => Ok(Box::new(build(Box::new(expression)))),
+                    }
+                }
+                Some(Entry::Custom(notation)) => {
+                    let precedence = u16::from(notation.precedence());
+                    let operand = self.expression(precedence, nesting + 1)?;
+                    Ok(Box::new(Expression::Operator(notation.clone(), vec![*operand])))
+                }
+                _ => Err(ParseError::no_token(span, self.source)),
+            },
+        }
+    }
+
+    /// Parses the parenthesized arguments after the name of a function.
+    fn call(&mut self, name: String, nesting: usize) -> ParseResult<Box<Expression>> {
+        let open = self.next().map_or(self.last_span, |t| t.span);
+
+        let mut args = Vec::new();
+        if let Some(&Token::CloseParen) = self.peek() {
+            self.next();
+        } else {
+            loop {
+                let arg = self.expression(0, nesting + 1)?;
+                args.push(*arg);
+                match self.next() {
+                    Some(SpannedToken { token: Token::Comma, .. }) => (),
+                    Some(SpannedToken { token: Token::CloseParen, .. }) => break,
+                    Some(t) => return Err(self.stray(t)),
+                    None => return Err(ParseError::mismatch_paren(open, self.source)),
+                }
+            }
+        }
+
+        Ok(Box::new(Expression::Call(name, args)))
+    }
+
+    fn close_paren(&mut self, open: Span) -> ParseResult<()> {
+        match self.next() {
+            Some(SpannedToken { token: Token::CloseParen, .. }) => Ok(()),
+            Some(t) => Err(self.stray(t)),
+            None => Err(ParseError::mismatch_paren(open, self.source)),
+        }
+    }
+
+    /// Error for a token found after a complete operand where no operator takes it.
+    fn stray(&self, t: SpannedToken) -> ParseError {
+        match t.token {
+            Token::CloseParen => ParseError::mismatch_paren(t.span, self.source),
+            Token::Comma | Token::Colon | Token::Equal => {
+                ParseError::unexpected_token(t.span, self.source)
+            }
+            _ => ParseError::too_much_token(t.span, self.source),
+        }
+    }
+}
+
+/// Minimum precedence of the operators in the right operand of `entry`.
+fn right_precedence(entry: Entry) -> u16 {
+    match entry.associativity() {
+        Associativity::Left => u16::from(entry.precedence()) + 1,
+        Associativity::Right => u16::from(entry.precedence()),
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use expression::operator::Associativity;
+    use expression::operator::Notation;
+    use expression::token::parse_token;
+    use expression::token::parse_token_with;
+
+    fn run(source: &str) -> ParseResult<String> {
+        let tokens = parse_token(source).expect("Test returns Err().");
+        parse(tokens, source, &OperatorTable::new()).map(|expr| postfix(&expr))
+    }
+
+    /// Parses `source` with `<+>` (infix 5 left), `<^>` (infix 14 right),
+    /// `sq` (prefix 13) and `%%` (postfix 15) declared.
+    fn run_custom(source: &str) -> ParseResult<String> {
+        let mut operators = OperatorTable::new();
+        let notations = vec![
+            Notation::infix("<+>", 5, Associativity::Left),
+            Notation::infix("<^>", 14, Associativity::Right),
+            Notation::prefix("sq", 13),
+            Notation::postfix("%%", 15),
+        ];
+        for notation in notations {
+            operators.declare(notation.expect("Test returns Err()."));
+        }
+        let tokens = parse_token_with(source, &operators).expect("Test returns Err().");
+        parse(tokens, source, &operators).map(|expr| postfix(&expr))
+    }
+
+    fn run_err(source: &str) -> ParseError {
+        run(source).expect_err("Test should returns Err().")
+    }
+
+    /// Renders the tree in reverse polish notation, which shows how operands were grouped.
+    fn postfix(expr: &Expression) -> String {
+        let (op, operands): (&str, Vec<&Expression>) = match *expr {
+            Expression::Number(ref n) => return n.to_string(),
+            Expression::Decimal(ref s) => return s.clone(),
+            Expression::Variable(ref name) => return name.clone(),
+            Expression::Call(ref name, ref args) => {
+                let args: Vec<String> = args.iter().map(postfix).collect();
+                return format!("{} {}/{}", args.join(" "), name, args.len()).trim().to_string();
+            }
+            Expression::Add(ref l, ref r) => ("+", vec![l, r]),
+            Expression::Sub(ref l, ref r) => ("-", vec![l, r]),
+            Expression::Mult(ref l, ref r) => ("*", vec![l, r]),
+            Expression::Div(ref l, ref r) => ("/", vec![l, r]),
+            Expression::Mod(ref l, ref r) => ("%", vec![l, r]),
+            Expression::Pow(ref l, ref r) => ("^", vec![l, r]),
+            Expression::Neg(ref e) => ("neg", vec![e]),
+            Expression::BitAnd(ref l, ref r) => ("&", vec![l, r]),
+            Expression::BitOr(ref l, ref r) => ("|", vec![l, r]),
+            Expression::BitXor(ref l, ref r) => ("xor", vec![l, r]),
+            Expression::BitNot(ref e) => ("~", vec![e]),
+            Expression::Factorial(ref e) => ("fact", vec![e]),
+            Expression::Shl(ref l, ref r) => ("<<", vec![l, r]),
+            Expression::Shr(ref l, ref r) => (">>", vec![l, r]),
+            Expression::LogicalShr(ref l, ref r) => (">>>", vec![l, r]),
+            Expression::Eq(ref l, ref r) => ("==", vec![l, r]),
+            Expression::Ne(ref l, ref r) => ("!=", vec![l, r]),
+            Expression::Lt(ref l, ref r) => ("<", vec![l, r]),
+            Expression::Le(ref l, ref r) => ("<=", vec![l, r]),
+            Expression::Gt(ref l, ref r) => (">", vec![l, r]),
+            Expression::Ge(ref l, ref r) => (">=", vec![l, r]),
+            Expression::And(ref l, ref r) => ("&&", vec![l, r]),
+            Expression::Or(ref l, ref r) => ("||", vec![l, r]),
+            Expression::Not(ref e) => ("not", vec![e]),
+            Expression::Conditional(ref c, ref a, ref b) => ("?:", vec![c, a, b]),
+            Expression::Operator(ref notation, ref operands) => {
+                (notation.symbol(), operands.iter().collect())
+            }
+        };
+        let operands: Vec<String> = operands.into_iter().map(postfix).collect();
+        format!("{} {}", operands.join(" "), op)
+    }
+
+    //// parse ////
+
+    #[test]
+    fn parse_one_number() {
+        assert_eq!(run("1"), Ok("1".to_string()));
+    }
+
+    #[test]
+    fn parse_add_mul_pow() {
+        assert_eq!(run("1 + 2 * 3 ^ 4"), Ok("1 2 3 4 ^ * +".to_string()));
+    }
+
+    #[test]
+    fn parse_sub_div_mod() {
+        assert_eq!(run("1 - 2 / 3 % 4"), Ok("1 2 3 / 4 % -".to_string()));
+    }
+
+    #[test]
+    fn parse_left_and_right_associativity() {
+        assert_eq!(run("1 - 2 - 3"), Ok("1 2 - 3 -".to_string()));
+        assert_eq!(run("2 ^ 3 ^ 2"), Ok("2 3 2 ^ ^".to_string()));
+    }
+
+    #[test]
+    fn parse_paren() {
+        assert_eq!(run("1 * (2 + 3)"), Ok("1 2 3 + *".to_string()));
+    }
+
+    #[test]
+    fn parse_neg() {
+        assert_eq!(run("-5"), Ok("-5".to_string()));
+        assert_eq!(run("-2 ^ 2"), Ok("2 2 ^ neg".to_string()));
+        assert_eq!(run("3 * -2 + 1"), Ok("3 -2 * 1 +".to_string()));
+        assert_eq!(run("-(2 - 3)"), Ok("2 3 - neg".to_string()));
+        assert_eq!(run("-(5)"), Ok("5 neg".to_string()));
+        assert_eq!(run("- -5"), Ok("-5 neg".to_string()));
+    }
+
+    #[test]
+    fn parse_unary_plus() {
+        assert_eq!(run("+1 + +2"), Ok("1 2 +".to_string()));
+    }
+
+    #[test]
+    fn parse_bitwise_precedence() {
+        assert_eq!(run("1 | 2 xor 3 & 4 << 5 + 6"), Ok("1 2 3 4 5 6 + << & xor |".to_string()));
+    }
+
+    #[test]
+    fn parse_tilde() {
+        assert_eq!(run("~-1 >> 2"), Ok("-1 ~ 2 >>".to_string()));
+    }
+
+    #[test]
+    fn parse_tilde_after_operand() {
+        let err = run_err("1 ~ 2");
+
+        match err {
+            ref err @ ParseError::TooMuchToken { .. } => assert!(err.to_string().ends_with(
+                "入力: \"1 ~ 2\"\n         ^ 余分なトークン\n"
+            )),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_factorial() {
+        assert_eq!(run("-2 ^ 3!! + !1"), Ok("2 3 fact fact ^ neg 1 not +".to_string()));
+    }
+
+    #[test]
+    fn parse_factorial_before_operand() {
+        let err = run_err("3! 2");
+
+        match err {
+            ref err @ ParseError::TooMuchToken { .. } => assert!(err.to_string().ends_with(
+                "入力: \"3! 2\"\n          ^ 余分なトークン\n"
+            )),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_conditional() {
+        let result = run("a ? 1 : b ? 2 + 3 : 4");
+
+        assert_eq!(result, Ok("a 1 b 2 3 + 4 ?: ?:".to_string()));
+        assert_eq!(run("a ? b ? 1 : 2 : 3"), Ok("a b 1 2 ?: 3 ?:".to_string()));
+    }
+
+    #[test]
+    fn parse_colon_without_question() {
+        let err = run_err("(1 : 2)");
+
+        match err {
+            ref err @ ParseError::UnexpectedToken { .. } => assert!(err.to_string().ends_with(
+                "入力: \"(1 : 2)\"\n          ^ 不正なトークン\n"
+            )),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_unclosed_paren() {
+        let err = run_err("1 * (2 + 3");
+
+        match err {
+            ref err @ ParseError::MismatchParen { .. } => assert!(err.to_string().ends_with(
+                "入力: \"1 * (2 + 3\"\n           ^ 対応する括弧がありません\n"
+            )),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_unopened_paren() {
+        let err = run_err("(1) + 2)");
+
+        match err {
+            ref err @ ParseError::MismatchParen { .. } => assert!(err.to_string().ends_with(
+                "入力: \"(1) + 2)\"\n              ^ 対応する括弧がありません\n"
+            )),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_dangling_operator() {
+        let err = run_err("1 + 2 *");
+
+        match err {
+            ref err @ ParseError::NoToken { .. } => assert!(err.to_string().ends_with(
+                "入力: \"1 + 2 *\"\n             ^ 被演算子がありません\n"
+            )),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_missing_left_operand() {
+        let err = run_err("1 + * 2");
+
+        match err {
+            ref err @ ParseError::NoToken { .. } => assert!(err.to_string().ends_with(
+                "入力: \"1 + * 2\"\n           ^ 被演算子がありません\n"
+            )),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_empty_paren() {
+        let err = run_err("2 * ()");
+
+        match err {
+            ref err @ ParseError::NoToken { .. } => assert!(err.to_string().ends_with(
+                "入力: \"2 * ()\"\n           ^ 被演算子がありません\n"
+            )),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_too_much_token() {
+        let err = run_err("1 + 2 3");
+
+        match err {
+            ref err @ ParseError::TooMuchToken { .. } => assert!(err.to_string().ends_with(
+                "入力: \"1 + 2 3\"\n             ^ 余分なトークン\n"
+            )),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_call() {
+        assert_eq!(run("max(1, 2 + 3) * 4"), Ok("1 2 3 + max/2 4 *".to_string()));
+        assert_eq!(run("f(g(), (x))"), Ok("g/0 x f/2".to_string()));
+    }
+
+    #[test]
+    fn parse_comma_outside_call() {
+        let err = run_err("(1, 2)");
+
+        match err {
+            ref err @ ParseError::UnexpectedToken { .. } => assert!(err.to_string().ends_with(
+                "入力: \"(1, 2)\"\n         ^ 不正なトークン\n"
+            )),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_missing_argument() {
+        let err = run_err("max(1, )");
+
+        match err {
+            ref err @ ParseError::NoToken { .. } => assert!(err.to_string().ends_with(
+                "入力: \"max(1, )\"\n            ^ 被演算子がありません\n"
+            )),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_custom_operators() {
+        assert_eq!(run_custom("1 <+> 2 <+> 3 * 4"), Ok("1 2 <+> 3 4 * <+>".to_string()));
+        assert_eq!(run_custom("1 <^> 2 <^> 3 + 4"), Ok("1 2 3 <^> <^> 4 +".to_string()));
+        assert_eq!(run_custom("sq 2 %% <+> -1"), Ok("2 %% sq -1 <+>".to_string()));
+        assert_eq!(run_custom("sq sq (1 + 2)"), Ok("1 2 + sq sq".to_string()));
+    }
+
+    #[test]
+    fn parse_custom_operator_missing_operand() {
+        match run_custom("1 <+>").expect_err("Test should returns Err().") {
+            ParseError::NoToken { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+        match run_custom("%% 1").expect_err("Test should returns Err().") {
+            ParseError::NoToken { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_str_as_operand() {
+        match run_err("1 + \"x\"") {
+            ParseError::UnexpectedToken { .. } => (),
+            e => panic!("Unexcepted error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_too_deep() {
+        let parens = format!("{}1{}", "(".repeat(300), ")".repeat(300));
+        let prefixes = format!("{}1", "-".repeat(300));
+        let powers = format!("2{}", " ^ 2".repeat(300));
+
+        for source in &[parens, prefixes, powers] {
+            match run_err(source) {
+                ParseError::TooDeep { .. } => (),
+                e => panic!("Unexcepted error: {:?}", e),
+            }
+        }
+        let limit = format!("{}1{}", "(".repeat(MAX_NESTING), ")".repeat(MAX_NESTING));
+        assert!(run(&limit).is_ok());
+        // Left-associative chains do not nest.
+        let chain = format!("1{}", " + 1".repeat(300));
+        assert!(run(&chain).is_ok());
+    }
+
+    #[test]
+    fn parse_never_panics() {
+        let symbols = ["1", "x", "f(", "(", ")", ",", "-", "*", "!", "?", ":", "=", "~"];
+
+        // Every sequence of up to four symbols parses or fails without a panic.
+        let mut sources = vec![String::new()];
+        for _ in 0..4 {
+            let longer: Vec<String> = sources
+                .iter()
+                .flat_map(|source| {
+                    symbols.iter().map(move |symbol| format!("{} {}", source, symbol))
+                })
+                .collect();
+            for source in &longer {
+                let _ = run(source);
+            }
+            sources = longer;
+        }
+    }
+}
@@ -0,0 +1,495 @@
+use std::cmp;
+use std::fmt;
+
+use num_traits::Signed;
+
+use expression::Expression;
+use expression::operator;
+use expression::operator::Associativity;
+use expression::operator::Fixity;
+use expression::operator::Notation;
+
+
+/// How `Expression::render` writes an expression.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Style {
+    /// Infix source with only the parentheses needed to parse it back, e.g. `(1 + 2) * 3`.
+    #[default]
+    Minimal,
+    /// Infix source with every operation parenthesized, e.g. `((1 + 2) * 3)`.
+    Parenthesized,
+    /// Reverse polish notation, e.g. `1 2 + 3 *`. A call is written `name/arity`.
+    Rpn,
+    /// Prefix notation as S-expressions, e.g. `(* (+ 1 2) 3)`.
+    SExpression,
+}
+
+impl Expression {
+    /// Writes the expression in `style`. The infix styles parse back to the same expression,
+    /// given the operators declared at runtime in it.
+    pub fn render(&self, style: Style) -> String {
+        match style {
+            Style::Minimal => infix(self, false).text,
+            Style::Parenthesized => infix(self, true).text,
+            Style::Rpn => {
+                let mut words = Vec::new();
+                rpn(self, &mut words);
+                words.join(" ")
+            }
+            Style::SExpression => s_expression(self),
+        }
+    }
+}
+
+/// Writes the expression with minimal parentheses.
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.render(Style::Minimal))
+    }
+}
+
+/// Operator at the root of an expression.
+struct Op<'a> {
+    symbol: &'a str,
+    precedence: u8,
+    associativity: Associativity,
+    /// Declared at runtime. Its symbol is set apart from the operands by spaces.
+    custom: bool,
+}
+
+impl<'a> Op<'a> {
+    /// Minimum precedence of the operators in the right operand, as the parser requires.
+    fn right_precedence(&self) -> u16 {
+        match self.associativity {
+            Associativity::Left => u16::from(self.precedence) + 1,
+            Associativity::Right => u16::from(self.precedence),
+        }
+    }
+
+    /// Name in the notations without operator positions, where `-` and `!` would be ambiguous.
+    fn name(&self, fixity: Fixity) -> &'a str {
+        match (self.symbol, fixity, self.custom) {
+            ("-", Fixity::Prefix, false) => "neg",
+            ("!", Fixity::Prefix, false) => "not",
+            ("?", _, false) => "?:",
+            (symbol, _, _) => symbol,
+        }
+    }
+}
+
+/// Root of an expression.
+enum Shape<'a> {
+    /// Literal, variable or call, which never needs parentheses.
+    Leaf,
+    Prefix(Op<'a>, &'a Expression),
+    Infix(Op<'a>, &'a Expression, &'a Expression),
+    Postfix(Op<'a>, &'a Expression),
+    Ternary(Op<'a>, &'a Expression, &'a Expression, &'a Expression),
+}
+
+fn shape(expr: &Expression) -> Shape<'_> {
+    let binary = |symbol, left, right| Shape::Infix(builtin(symbol, Fixity::Infix), left, right);
+    match *expr {
+        Expression::Number(_)
+        | Expression::Decimal(_)
+        | Expression::Variable(_)
+        | Expression::Call(..) => Shape::Leaf,
+        Expression::Add(ref l, ref r) => binary("+", l, r),
+        Expression::Sub(ref l, ref r) => binary("-", l, r),
+        Expression::Mult(ref l, ref r) => binary("*", l, r),
+        Expression::Div(ref l, ref r) => binary("/", l, r),
+        Expression::Mod(ref l, ref r) => binary("%", l, r),
+        Expression::Pow(ref l, ref r) => binary("^", l, r),
+        Expression::BitAnd(ref l, ref r) => binary("&", l, r),
+        Expression::BitOr(ref l, ref r) => binary("|", l, r),
+        Expression::BitXor(ref l, ref r) => binary("xor", l, r),
+        Expression::Shl(ref l, ref r) => binary("<<", l, r),
+        Expression::Shr(ref l, ref r) => binary(">>", l, r),
+        Expression::LogicalShr(ref l, ref r) => binary(">>>", l, r),
+        Expression::Eq(ref l, ref r) => binary("==", l, r),
+        Expression::Ne(ref l, ref r) => binary("!=", l, r),
+        Expression::Lt(ref l, ref r) => binary("<", l, r),
+        Expression::Le(ref l, ref r) => binary("<=", l, r),
+        Expression::Gt(ref l, ref r) => binary(">", l, r),
+        Expression::Ge(ref l, ref r) => binary(">=", l, r),
+        Expression::And(ref l, ref r) => binary("&&", l, r),
+        Expression::Or(ref l, ref r) => binary("||", l, r),
+        Expression::Neg(ref e) => Shape::Prefix(builtin("-", Fixity::Prefix), e),
+        Expression::BitNot(ref e) => Shape::Prefix(builtin("~", Fixity::Prefix), e),
+        Expression::Not(ref e) => Shape::Prefix(builtin("!", Fixity::Prefix), e),
+        Expression::Factorial(ref e) => Shape::Postfix(builtin("!", Fixity::Postfix), e),
+        Expression::Conditional(ref c, ref a, ref b) => {
+            Shape::Ternary(builtin("?", Fixity::Infix), c, a, b)
+        }
+        Expression::Operator(ref notation, ref operands) => custom(notation, operands),
+    }
+}
+
+fn builtin(symbol: &str, fixity: Fixity) -> Op<'static> {
+    let op = operator::builtin(symbol, fixity).expect("Every operation has a built-in operator.");
+    Op {
+        symbol: op.symbol,
+        precedence: op.precedence,
+        associativity: op.associativity,
+        custom: false,
+    }
+}
+
+/// Operands not matching the fixity, which the parser never makes, are written like a call.
+fn custom<'a>(notation: &'a Notation, operands: &'a [Expression]) -> Shape<'a> {
+    let op = Op {
+        symbol: notation.symbol(),
+        precedence: notation.precedence(),
+        associativity: notation.associativity(),
+        custom: true,
+    };
+    match (notation.fixity(), operands) {
+        (Fixity::Prefix, [operand]) => Shape::Prefix(op, operand),
+        (Fixity::Infix, [left, right]) => Shape::Infix(op, left, right),
+        (Fixity::Postfix, [operand]) => Shape::Postfix(op, operand),
+        _ => Shape::Leaf,
+    }
+}
+
+/// No operator outside parentheses.
+const NONE: u16 = u16::MAX;
+
+/// Infix source of an expression, and how it binds to the operators around it.
+struct Source {
+    text: String,
+    /// Lowest precedence of the operators outside parentheses which follow the first operand.
+    /// The text is read back as one operand only where operators of this precedence are taken.
+    inner: u16,
+    /// An operator of this precedence or higher written after the text
+    /// would be taken into its last operand.
+    open: u16,
+    /// Starts with a prefix operator.
+    leading_symbol: bool,
+    /// Ends with a postfix operator.
+    trailing_symbol: bool,
+}
+
+impl Source {
+    fn leaf(text: String) -> Source {
+        Source { text, inner: NONE, open: NONE, leading_symbol: false, trailing_symbol: false }
+    }
+
+    fn parenthesized(self) -> Source {
+        Source::leaf(format!("({})", self.text))
+    }
+
+    fn parenthesized_if<F: FnOnce(&Source) -> bool>(self, condition: F) -> Source {
+        if condition(&self) {
+            self.parenthesized()
+        } else {
+            self
+        }
+    }
+}
+
+/// Writes `expr` as infix source, with every operation parenthesized if `all` is set.
+fn infix(expr: &Expression, all: bool) -> Source {
+    let source = match shape(expr) {
+        Shape::Leaf => match *expr {
+            // A negative literal is read back like a negation.
+            Expression::Number(ref n) if n.is_negative() => {
+                return Source {
+                    text: n.to_string(),
+                    inner: NONE,
+                    open: u16::from(builtin("-", Fixity::Prefix).precedence),
+                    leading_symbol: true,
+                    trailing_symbol: false,
+                };
+            }
+            _ => return Source::leaf(leaf(expr, all)),
+        },
+        Shape::Prefix(op, operand) => {
+            let precedence = u16::from(op.precedence);
+            // A minus sign right before a number would be read as a negative literal.
+            let literal = !op.custom && op.symbol == "-";
+            let literal = literal && matches!(*operand, Expression::Number(_));
+            let operand = infix(operand, all);
+            let operand =
+                operand.parenthesized_if(|s| s.inner < precedence || literal && !s.leading_symbol);
+            let space = if op.custom || operand.leading_symbol { " " } else { "" };
+            Source {
+                text: format!("{}{}{}", op.symbol, space, operand.text),
+                inner: NONE,
+                open: cmp::min(precedence, operand.open),
+                leading_symbol: true,
+                trailing_symbol: operand.trailing_symbol,
+            }
+        }
+        Shape::Infix(op, left, right) => {
+            let precedence = u16::from(op.precedence);
+            let left = infix(left, all);
+            let left = left.parenthesized_if(|s| precedence >= s.open);
+            let right = infix(right, all);
+            let right = right.parenthesized_if(|s| s.inner < op.right_precedence());
+            Source {
+                text: format!("{} {} {}", left.text, op.symbol, right.text),
+                inner: cmp::min(precedence, left.inner),
+                open: cmp::min(op.right_precedence(), right.open),
+                leading_symbol: left.leading_symbol,
+                trailing_symbol: right.trailing_symbol,
+            }
+        }
+        Shape::Postfix(op, operand) => {
+            let precedence = u16::from(op.precedence);
+            let operand = infix(operand, all);
+            let operand = operand.parenthesized_if(|s| precedence >= s.open);
+            let space = if op.custom || operand.trailing_symbol { " " } else { "" };
+            Source {
+                text: format!("{}{}{}", operand.text, space, op.symbol),
+                inner: cmp::min(precedence, operand.inner),
+                open: NONE,
+                leading_symbol: operand.leading_symbol,
+                trailing_symbol: true,
+            }
+        }
+        Shape::Ternary(op, condition, then, otherwise) => {
+            let precedence = u16::from(op.precedence);
+            let condition = infix(condition, all);
+            let condition = condition.parenthesized_if(|s| precedence >= s.open);
+            // The middle operand ends at `:` whatever it contains.
+            let then = infix(then, all);
+            let otherwise = infix(otherwise, all);
+            let otherwise = otherwise.parenthesized_if(|s| s.inner < op.right_precedence());
+            Source {
+                text: format!("{} ? {} : {}", condition.text, then.text, otherwise.text),
+                inner: cmp::min(precedence, condition.inner),
+                open: cmp::min(op.right_precedence(), otherwise.open),
+                leading_symbol: condition.leading_symbol,
+                trailing_symbol: otherwise.trailing_symbol,
+            }
+        }
+    };
+
+    source.parenthesized_if(|_| all)
+}
+
+/// Name of the operation at the root of `expr` and its operands, for the notations
+/// without operator positions. The flag tells a call from an operator.
+fn operation(expr: &Expression) -> (String, Vec<&Expression>, bool) {
+    match shape(expr) {
+        Shape::Prefix(op, e) => (op.name(Fixity::Prefix).to_string(), vec![e], false),
+        Shape::Infix(op, l, r) => (op.name(Fixity::Infix).to_string(), vec![l, r], false),
+        Shape::Postfix(op, e) => (op.name(Fixity::Postfix).to_string(), vec![e], false),
+        Shape::Ternary(op, c, a, b) => (op.name(Fixity::Infix).to_string(), vec![c, a, b], false),
+        Shape::Leaf => match *expr {
+            Expression::Number(ref n) => (n.to_string(), vec![], false),
+            Expression::Decimal(ref s) => (s.clone(), vec![], false),
+            Expression::Variable(ref name) => (name.clone(), vec![], false),
+            Expression::Call(ref name, ref args) => (name.clone(), args.iter().collect(), true),
+            Expression::Operator(ref notation, ref operands) => {
+                (notation.symbol().to_string(), operands.iter().collect(), true)
+            }
+            _ => unreachable!("Only literals, variables and calls are leaves."),
+        },
+    }
+}
+
+/// Writes a literal, a variable or a call as infix source.
+fn leaf(expr: &Expression, all: bool) -> String {
+    match operation(expr) {
+        (name, args, true) => {
+            let args: Vec<String> = args.into_iter().map(|arg| infix(arg, all).text).collect();
+            format!("{}({})", name, args.join(", "))
+        }
+        (name, _, false) => name,
+    }
+}
+
+fn rpn(expr: &Expression, words: &mut Vec<String>) {
+    let (name, operands, call) = operation(expr);
+    let count = operands.len();
+    for operand in operands {
+        rpn(operand, words);
+    }
+    if call {
+        words.push(format!("{}/{}", name, count));
+    } else {
+        words.push(name);
+    }
+}
+
+fn s_expression(expr: &Expression) -> String {
+    match operation(expr) {
+        (name, ref operands, false) if operands.is_empty() => name,
+        (name, operands, _) => {
+            let words: Vec<String> = operands.into_iter().map(s_expression).collect();
+            format!("({})", [vec![name], words].concat().join(" "))
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use expression::operator::OperatorTable;
+    use expression::parse_expr;
+    use expression::parse_expr_with;
+    use proptest::prelude::*;
+
+    fn render(source: &str, style: Style) -> String {
+        parse_expr(source).expect("Test returns Err().").render(style)
+    }
+
+    /// `<+>` and `-+-` mix associativities at the precedence of `xor` and `+`,
+    /// `zz` and `%%` bind looser than most operators and `zpc` tighter than any.
+    fn operators() -> OperatorTable {
+        let mut operators = OperatorTable::new();
+        let notations = vec![
+            Notation::infix("<+>", 5, Associativity::Right),
+            Notation::infix("-+-", 10, Associativity::Right),
+            Notation::infix("^^", 14, Associativity::Left),
+            Notation::prefix("zz", 3),
+            Notation::prefix("$", 14),
+            Notation::postfix("%%", 2),
+            Notation::postfix("zpc", 16),
+        ];
+        for notation in notations {
+            operators.declare(notation.expect("Test returns Err()."));
+        }
+        operators
+    }
+
+    //// Style::Minimal ////
+
+    #[test]
+    fn render_minimal_parentheses() {
+        let cases = [
+            ("((1 + 2)) * 3", "(1 + 2) * 3"),
+            ("(1 - 2) - (3 - 4)", "1 - 2 - (3 - 4)"),
+            ("(2 ^ 3) ^ (4 ^ 5)", "(2 ^ 3) ^ 4 ^ 5"),
+            ("-(2 ^ 2) + (-2) ^ 2 + (-(2)) ^ 2", "-2 ^ 2 + (-2) ^ 2 + (-(2)) ^ 2"),
+            ("-(x!) * (-x)! * -(-x)", "-x! * (-x)! * - -x"),
+            ("!(!(a == b)) && (!a || b)", "! !(a == b) && (!a || b)"),
+            ("(a ? b : c) ? (d ? e : f) : (g ? h : i)", "(a ? b : c) ? d ? e : f : g ? h : i"),
+            ("1 xor (2 & 3) | (4 | 5) << 6", "1 xor 2 & 3 | (4 | 5) << 6"),
+            ("max((1 + 2), (3), f())", "max(1 + 2, 3, f())"),
+            ("f((-3)) - (-3) - -(3) - -(-3)", "f(-3) - -3 - -(3) - - -3"),
+        ];
+
+        for &(source, expected) in &cases {
+            assert_eq!(render(source, Style::Minimal), expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn render_custom_operators() {
+        let operators = operators();
+        let cases = [
+            ("(1 <+> 2) <+> (3 <+> 4)", "(1 <+> 2) <+> 3 <+> 4"),
+            ("(1 + 2) -+- (3 + 4)", "1 + 2 -+- 3 + 4"),
+            ("(1 -+- 2) + 3", "(1 -+- 2) + 3"),
+            ("(zz 1) + zz (2 + 3)", "(zz 1) + zz 2 + 3"),
+            ("(1 + 2) %% * (3 %%)", "1 + 2 %% * (3 %%)"),
+            ("$ ($ 2 ^^ 3) zpc", "$ ($ 2 ^^ 3) zpc"),
+            ("$ (2 zpc) ^^ (3 ^^ 4)", "$ 2 zpc ^^ (3 ^^ 4)"),
+        ];
+
+        for &(source, expected) in &cases {
+            let expr = parse_expr_with(source, &operators).expect("Test returns Err().");
+            assert_eq!(expr.to_string(), expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn render_display() {
+        let expr = parse_expr("(x + 1) * f(y, 2)").expect("Test returns Err().");
+
+        assert_eq!(format!("{}", expr), "(x + 1) * f(y, 2)");
+    }
+
+    //// Other styles ////
+
+    #[test]
+    fn render_other_styles() {
+        let source = "1 + 2 * -x! > max(1, 2) ? 1.5 : (-3)";
+
+        assert_eq!(
+            render(source, Style::Parenthesized),
+            "(((1 + (2 * (-(x!)))) > max(1, 2)) ? 1.5 : -3)"
+        );
+        assert_eq!(render(source, Style::Rpn), "1 2 x ! neg * + 1 2 max/2 > 1.5 -3 ?:");
+        assert_eq!(
+            render(source, Style::SExpression),
+            "(?: (> (+ 1 (* 2 (neg (! x)))) (max 1 2)) 1.5 -3)"
+        );
+        assert_eq!(render("!a + f()", Style::Rpn), "a not f/0 +");
+        assert_eq!(render("!a + f()", Style::SExpression), "(+ (not a) (f))");
+    }
+
+    //// round trip ////
+
+    type Binary = fn(Box<Expression>, Box<Expression>) -> Expression;
+
+    const BINARY: &[Binary] = &[
+        Expression::Add,
+        Expression::Sub,
+        Expression::Mult,
+        Expression::Div,
+        Expression::Mod,
+        Expression::Pow,
+        Expression::BitAnd,
+        Expression::BitOr,
+        Expression::BitXor,
+        Expression::Shl,
+        Expression::Shr,
+        Expression::LogicalShr,
+        Expression::Eq,
+        Expression::Ne,
+        Expression::Lt,
+        Expression::Le,
+        Expression::Gt,
+        Expression::Ge,
+        Expression::And,
+        Expression::Or,
+    ];
+
+    const UNARY: &[fn(Box<Expression>) -> Expression] =
+        &[Expression::Neg, Expression::BitNot, Expression::Not, Expression::Factorial];
+
+    /// Expressions the parser can make, with the operators of `operators()`.
+    fn expression() -> impl Strategy<Value = Expression> {
+        let custom: Vec<Notation> = operators().custom().to_vec();
+        let leaf = prop_oneof![
+            any::<i64>().prop_map(|n| Expression::Number(n.into())),
+            "[0-9]{1,3}(\\.[0-9]{0,3}|\\.?[0-9]{0,3}e-?[0-9]{1,2})".prop_map(Expression::Decimal),
+            // Leaves out `xor` and the word operators.
+            "[a-w][a-z0-9_]{0,2}".prop_map(Expression::Variable),
+        ];
+        leaf.prop_recursive(6, 48, 3, move |inner| {
+            let custom = custom.clone();
+            prop_oneof![
+                (0..BINARY.len(), inner.clone(), inner.clone())
+                    .prop_map(|(i, l, r)| BINARY[i](Box::new(l), Box::new(r))),
+                (0..UNARY.len(), inner.clone()).prop_map(|(i, e)| UNARY[i](Box::new(e))),
+                (inner.clone(), inner.clone(), inner.clone()).prop_map(|(c, a, b)| {
+                    Expression::Conditional(Box::new(c), Box::new(a), Box::new(b))
+                }),
+                ("[a-w][a-z]{0,2}", prop::collection::vec(inner.clone(), 0..3))
+                    .prop_map(|(name, args)| Expression::Call(name, args)),
+                (0..custom.len(), inner.clone(), inner.clone()).prop_map(move |(i, l, r)| {
+                    let notation = custom[i].clone();
+                    let operands = if notation.arity() == 2 { vec![l, r] } else { vec![l] };
+                    Expression::Operator(notation, operands)
+                }),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn render_round_trip(expr in expression()) {
+            let operators = operators();
+            for &style in &[Style::Minimal, Style::Parenthesized] {
+                let source = expr.render(style);
+                let parsed = parse_expr_with(&source, &operators);
+                prop_assert_eq!(parsed.ok(), Some(expr.clone()), "{}", source);
+            }
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::cmp;
+
+
+/// Byte range of a token in the source string.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    /// Renders `source` after `caption` with carets under this span, followed by `label`.
+    pub fn annotate(&self, source: &str, caption: &str, label: &str) -> String {
+        let start = cmp::min(self.start, source.len());
+        let end = cmp::max(start, cmp::min(self.end, source.len()));
+
+        // The source is quoted after `caption: "`, whose width depends on the language.
+        let indent = " ".repeat(display_width(caption) + 3);
+        let filler = " ".repeat(display_width(&source[..start]));
+        let carets = "^".repeat(cmp::max(1, display_width(&source[start..end])));
+
+        let mut s = String::new();
+        s += &format!("{}: \"{}\"\n", caption, source);
+        s += &format!("{}{}{} {}\n", indent, filler, carets, label);
+        s
+    }
+}
+
+/// Combining marks and other characters drawn over the previous one.
+const ZERO_WIDTH: &[(u32, u32)] = &[
+    (0x0300, 0x036F),
+    (0x0483, 0x0489),
+    (0x0591, 0x05BD),
+    (0x0610, 0x061A),
+    (0x064B, 0x065F),
+    (0x1AB0, 0x1AFF),
+    (0x1DC0, 0x1DFF),
+    (0x200B, 0x200F),
+    (0x20D0, 0x20FF),
+    (0x3099, 0x309A),
+    (0xFE00, 0xFE0F),
+    (0xFE20, 0xFE2F),
+    (0xE0100, 0xE01EF),
+];
+
+/// Characters of East Asian Width `W` or `F`, such as kanji, kana and full-width forms.
+const FULL_WIDTH: &[(u32, u32)] = &[
+    (0x1100, 0x115F),
+    (0x231A, 0x231B),
+    (0x2329, 0x232A),
+    (0x2E80, 0x303E),
+    (0x3041, 0x33FF),
+    (0x3400, 0x4DBF),
+    (0x4E00, 0x9FFF),
+    (0xA000, 0xA4CF),
+    (0xA960, 0xA97F),
+    (0xAC00, 0xD7A3),
+    (0xF900, 0xFAFF),
+    (0xFE10, 0xFE19),
+    (0xFE30, 0xFE6F),
+    (0xFF00, 0xFF60),
+    (0xFFE0, 0xFFE6),
+    (0x1F300, 0x1F64F),
+    (0x1F900, 0x1F9FF),
+    (0x20000, 0x2FFFD),
+    (0x30000, 0x3FFFD),
+];
+
+/// Columns taken by `s` in a terminal.
+fn display_width(s: &str) -> usize {
+    s.chars().map(char_width).sum()
+}
+
+fn char_width(c: char) -> usize {
+    let in_ranges = |ranges: &[(u32, u32)]| {
+        ranges.iter().any(|&(first, last)| first <= c as u32 && c as u32 <= last)
+    };
+    if c.is_ascii() {
+        1
+    } else if in_ranges(ZERO_WIDTH) {
+        0
+    } else if in_ranges(FULL_WIDTH) {
+        2
+    } else {
+        1
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    //// annotate ////
+
+    #[test]
+    fn annotate_one_char() {
+        let s = Span::new(2, 3).annotate("1 ? 2", "入力", "不正な文字");
+        assert_eq!(s, "入力: \"1 ? 2\"\n         ^ 不正な文字\n");
+    }
+
+    #[test]
+    fn annotate_multi_char() {
+        let s = Span::new(4, 7).annotate("1 + 123", "入力", "ここ");
+        assert_eq!(s, "入力: \"1 + 123\"\n           ^^^ ここ\n");
+    }
+
+    #[test]
+    fn annotate_end_of_input() {
+        let s = Span::new(3, 3).annotate("1 +", "入力", "ここ");
+        assert_eq!(s, "入力: \"1 +\"\n          ^ ここ\n");
+    }
+
+    #[test]
+    fn annotate_counts_display_width() {
+        let s = Span::new(4, 5).annotate("１+?", "入力", "ここ");
+        assert_eq!(s, "入力: \"１+?\"\n          ^ ここ\n");
+    }
+
+    #[test]
+    fn annotate_full_width_span() {
+        let s = Span::new(4, 7).annotate("1 + ＃", "入力", "不正な文字");
+        assert_eq!(s, "入力: \"1 + ＃\"\n           ^^ 不正な文字\n");
+    }
+
+    #[test]
+    fn annotate_narrow_non_ascii() {
+        let s = Span::new(3, 4).annotate("é+?", "input", "here");
+        assert_eq!(s, "input: \"é+?\"\n          ^ here\n");
+    }
+
+    #[test]
+    fn annotate_combining_mark() {
+        let s = Span::new(4, 5).annotate("e\u{301}+?", "input", "here");
+        assert_eq!(s, "input: \"e\u{301}+?\"\n          ^ here\n");
+    }
+
+    #[test]
+    fn annotate_ascii_caption() {
+        let s = Span::new(2, 3).annotate("1 ? 2", "input", "here");
+        assert_eq!(s, "input: \"1 ? 2\"\n          ^ here\n");
+    }
+}
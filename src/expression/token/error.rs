@@ -2,47 +2,90 @@ use std::error;
 use std::fmt;
 use std::result;
 
-#[derive(Debug)]
+use expression::span::Span;
+use message::Lang;
+use message::Message;
+use message::MessageKind;
+
+#[derive(Debug, PartialEq)]
 pub enum TokenError {
-    InvalidChar(String),
+    /// Character at byte offset `position` of `input` that starts no token.
+    InvalidChar { position: usize, found: char, input: String },
+    /// Character at `position` that is not a digit of a literal in `radix`.
+    /// `position` is the end of the literal when it has no digits.
+    InvalidDigit { position: usize, radix: u32, input: String },
 }
 
 impl TokenError {
-    pub fn new_invalid_char(at: usize, source: &str) -> TokenError {
-        let filler = String::from_utf8(vec![b' '; at]).unwrap();
+    pub fn new_invalid_char(at: usize, input: &str) -> TokenError {
+        TokenError::InvalidChar {
+            position: at,
+            found: input[at..].chars().next().unwrap_or('\0'),
+            input: input.to_string(),
+        }
+    }
+
+    pub fn new_invalid_digit(at: usize, radix: u32, input: &str) -> TokenError {
+        TokenError::InvalidDigit { position: at, radix, input: input.to_string() }
+    }
 
-        let mut s = String::new();
-        s = s + &format!("トークン化できない入力が検出されました。\n");
-        s = s + &format!("入力: \"{}\"\n", source);
-        s = s + &format!("       {}^ 不正な文字\n", filler);
-        TokenError::InvalidChar(s)
+    pub fn span(&self) -> Span {
+        match *self {
+            TokenError::InvalidChar { position, found, .. } => {
+                Span::new(position, position + found.len_utf8())
+            }
+            TokenError::InvalidDigit { position, ref input, .. } => {
+                let width = input[position..].chars().next().map_or(0, |c| c.len_utf8());
+                Span::new(position, position + width)
+            }
+        }
     }
 
-    pub fn get_message(&self) -> &str {
+    /// Stable identifier of the kind of this error.
+    pub fn code(&self) -> &'static str {
         match *self {
-            TokenError::InvalidChar(ref message) => message,
+            TokenError::InvalidChar { .. } => "invalid_char",
+            TokenError::InvalidDigit { .. } => "invalid_digit",
         }
     }
+
+    pub fn message(&self) -> Message {
+        match *self {
+            TokenError::InvalidChar { ref input, .. } => Message::new(MessageKind::InvalidChar)
+                .annotate(self.span(), input, MessageKind::InvalidCharLabel),
+            TokenError::InvalidDigit { radix, ref input, .. } => {
+                Message::new(MessageKind::InvalidDigit).arg(radix).annotate(
+                    self.span(),
+                    input,
+                    MessageKind::InvalidDigitLabel,
+                )
+            }
+        }
+    }
+
+    /// Message in `lang`.
+    pub fn localize(&self, lang: Lang) -> String {
+        self.message().render(lang)
+    }
 }
 
 pub type TokenResult<T> = result::Result<T, TokenError>;
 
 impl fmt::Display for TokenError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match *self {
-            TokenError::InvalidChar(ref message) => write!(f, "{}", message),
-        }
+        write!(f, "{}", self.message())
     }
 }
 
 impl error::Error for TokenError {
     fn description(&self) -> &str {
         match *self {
-            TokenError::InvalidChar(_) => "Invalid token found.",
+            TokenError::InvalidChar { .. } => "Invalid token found.",
+            TokenError::InvalidDigit { .. } => "Invalid digit in a number.",
         }
     }
 
-    fn cause(&self) -> Option<&error::Error> {
+    fn cause(&self) -> Option<&dyn error::Error> {
         None
     }
 }
@@ -1,54 +1,69 @@
 pub mod error;
 
+use num_bigint::BigInt;
 use regex::Regex;
 use std::vec::Vec;
 
+use expression::operator::OperatorTable;
+use expression::span::Span;
 use expression::token::error::TokenError;
 use expression::token::error::TokenResult;
 
 #[derive(Debug, PartialEq)]
 pub enum Token {
-    Number(i64),
+    Number(BigInt),
+    Decimal(String),
+    Ident(String),
+    Equal,
+    Comma,
     Plus,
     Hyphen,
     Asterisk,
     Slash,
     Percent,
     Hat,
+    Ampersand,
+    Pipe,
+    /// Keyword `xor`. `^` is the power operator.
+    Xor,
+    Tilde,
+    ShiftLeft,
+    ShiftRight,
+    /// `>>>`, shifting zeros into the sign bit.
+    LogicalShiftRight,
+    DoubleEqual,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    DoubleAmpersand,
+    DoublePipe,
+    Bang,
+    /// `?` of a conditional `c ? a : b`.
+    Question,
+    /// `:` of a conditional.
+    Colon,
+    /// Symbol of an operator declared at runtime.
+    Operator(String),
+    /// Quoted text, which names an operator in its declaration.
+    Str(String),
     OpenParen,
     CloseParen,
 }
 
+/// Token with its position in the source string.
 #[derive(Debug, PartialEq)]
-pub enum Associativity {
-    Left,
-    Right,
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
 }
 
-impl Token {
-    pub fn priority(&self) -> Option<u8> {
-        match *self {
-            Token::Plus => Some(1),
-            Token::Hyphen => Some(1),
-            Token::Asterisk => Some(3),
-            Token::Slash => Some(3),
-            Token::Percent => Some(2),
-            Token::Hat => Some(4),
-            _ => None,
-        }
-    }
-
-    pub fn associativity(&self) -> Option<Associativity> {
-        match *self {
-            Token::Number(_) => None,
-            Token::Plus => Some(Associativity::Left),
-            Token::Hyphen => Some(Associativity::Left),
-            Token::Asterisk => Some(Associativity::Left),
-            Token::Slash => Some(Associativity::Left),
-            Token::Percent => Some(Associativity::Left),
-            Token::Hat => Some(Associativity::Right),
-            Token::OpenParen => None,
-            Token::CloseParen => None,
+impl SpannedToken {
+    pub fn new(token: Token, start: usize, end: usize) -> SpannedToken {
+        SpannedToken {
+            token,
+            span: Span::new(start, end),
         }
     }
 }
@@ -59,12 +74,41 @@ lazy_static! {
         Regex::new(r"^[ \t]+").unwrap()
     };
 
+    static ref REG_DECIMAL: Regex = {
+        Regex::new(concat!(
+            r"^(?:[0-9][0-9_]*\.(?:[0-9][0-9_]*)?(?:[eE][+-]?[0-9][0-9_]*)?",
+            r"|\.[0-9][0-9_]*(?:[eE][+-]?[0-9][0-9_]*)?",
+            r"|[0-9][0-9_]*[eE][+-]?[0-9][0-9_]*)",
+        )).unwrap()
+    };
+
+    /// Integer with a radix prefix. Invalid digits are matched too and reported afterwards.
+    static ref REG_RADIX_NUMBER: Regex = {
+        Regex::new(r"^0[xXoObB][0-9A-Za-z_]*").unwrap()
+    };
+
     static ref REG_NUMBER: Regex = {
-        Regex::new(r"^[0-9]+").unwrap()
+        Regex::new(r"^[0-9][0-9_]*").unwrap()
     };
 
-    static ref REG_NEG_NUMBER: Regex = {
-        Regex::new(r"^\(-[0-9]+\)").unwrap()
+    static ref REG_STR: Regex = {
+        Regex::new(r#"^"[^"]*""#).unwrap()
+    };
+
+    static ref REG_IDENT: Regex = {
+        Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*").unwrap()
+    };
+
+    static ref REG_DOUBLE_EQUAL: Regex = {
+        Regex::new(r"^==").unwrap()
+    };
+
+    static ref REG_EQUAL: Regex = {
+        Regex::new(r"^=").unwrap()
+    };
+
+    static ref REG_COMMA: Regex = {
+        Regex::new(r"^,").unwrap()
     };
 
     static ref REG_PLUS: Regex = {
@@ -91,6 +135,70 @@ lazy_static! {
         Regex::new(r"^\^").unwrap()
     };
 
+    static ref REG_NOT_EQUAL: Regex = {
+        Regex::new(r"^!=").unwrap()
+    };
+
+    static ref REG_BANG: Regex = {
+        Regex::new(r"^!").unwrap()
+    };
+
+    static ref REG_DOUBLE_AMPERSAND: Regex = {
+        Regex::new(r"^&&").unwrap()
+    };
+
+    static ref REG_DOUBLE_PIPE: Regex = {
+        Regex::new(r"^\|\|").unwrap()
+    };
+
+    static ref REG_AMPERSAND: Regex = {
+        Regex::new(r"^&").unwrap()
+    };
+
+    static ref REG_PIPE: Regex = {
+        Regex::new(r"^\|").unwrap()
+    };
+
+    static ref REG_TILDE: Regex = {
+        Regex::new(r"^~").unwrap()
+    };
+
+    static ref REG_SHIFT_LEFT: Regex = {
+        Regex::new(r"^<<").unwrap()
+    };
+
+    static ref REG_LOGICAL_SHIFT_RIGHT: Regex = {
+        Regex::new(r"^>>>").unwrap()
+    };
+
+    static ref REG_SHIFT_RIGHT: Regex = {
+        Regex::new(r"^>>").unwrap()
+    };
+
+    static ref REG_LESS_EQUAL: Regex = {
+        Regex::new(r"^<=").unwrap()
+    };
+
+    static ref REG_LESS: Regex = {
+        Regex::new(r"^<").unwrap()
+    };
+
+    static ref REG_GREATER_EQUAL: Regex = {
+        Regex::new(r"^>=").unwrap()
+    };
+
+    static ref REG_GREATER: Regex = {
+        Regex::new(r"^>").unwrap()
+    };
+
+    static ref REG_QUESTION: Regex = {
+        Regex::new(r"^\?").unwrap()
+    };
+
+    static ref REG_COLON: Regex = {
+        Regex::new(r"^:").unwrap()
+    };
+
     static ref REG_OPEN_PAREN: Regex = {
         Regex::new(r"^\(").unwrap()
     };
@@ -100,114 +208,230 @@ lazy_static! {
     };
 }
 
-pub fn parse_token(str: &str) -> TokenResult<Vec<Token>> {
-    let mut tokens: Vec<Token> = Vec::new();
+pub fn parse_token(str: &str) -> TokenResult<Vec<SpannedToken>> {
+    parse_token_with(str, &OperatorTable::new())
+}
+
+/// Splits `str` into tokens, recognizing the operators declared in `operators`.
+/// A declared symbol takes precedence over built-in operators it starts with.
+pub fn parse_token_with(str: &str, operators: &OperatorTable) -> TokenResult<Vec<SpannedToken>> {
+    let mut tokens: Vec<SpannedToken> = Vec::new();
     let mut str_left = str;
-    let mut pos = 0;
 
     loop {
-        str_left = str_left.trim();
+        str_left = str_left.trim_start();
+        let pos = str.len() - str_left.len();
 
-        if str_left == "" {
+        if str_left.is_empty() {
             return Ok(tokens);
         }
 
-        if let Some(mat) = REG_NUMBER.find(str_left) {
-            let mat_str = mat.as_str();
-            pos += mat_str.len();
+        if let Some(mat) = REG_DECIMAL.find(str_left) {
+            let token = Token::Decimal(mat.as_str().replace('_', ""));
+            tokens.push(SpannedToken::new(token, pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
 
-            let val = mat_str.parse::<i64>().unwrap();
-            tokens.push(Token::Number(val));
+        } else if let Some(mat) = REG_RADIX_NUMBER.find(str_left) {
+            let val = parse_radix(mat.as_str(), pos, str)?;
+            tokens.push(SpannedToken::new(Token::Number(val), pos, pos + mat.end()));
 
             str_left = str_tail_at(str_left, mat.end());
 
-        } else if let Some(mat) = REG_NEG_NUMBER.find(str_left) {
-            let mat_str = mat.as_str();
-            pos += mat_str.len();
+        } else if let Some(mat) = REG_NUMBER.find(str_left) {
+            let val = mat.as_str().replace('_', "").parse::<BigInt>().unwrap();
+            tokens.push(SpannedToken::new(Token::Number(val), pos, pos + mat.end()));
 
-            let mat_str = &mat_str[1..(mat_str.len() - 1)];
+            str_left = str_tail_at(str_left, mat.end());
 
-            let val = mat_str.parse::<i64>().unwrap();
-            tokens.push(Token::Number(val));
+        } else if let Some(mat) = REG_IDENT.find(str_left) {
+            let token = match mat.as_str() {
+                "xor" => Token::Xor,
+                word if operators.is_symbol(word) => Token::Operator(word.to_string()),
+                name => Token::Ident(name.to_string()),
+            };
+            tokens.push(SpannedToken::new(token, pos, pos + mat.end()));
 
             str_left = str_tail_at(str_left, mat.end());
 
-        } else if let Some(mat) = REG_PLUS.find(str_left) {
-            let mat_str = mat.as_str();
-            pos += mat_str.len();
+        } else if let Some(mat) = REG_STR.find(str_left) {
+            let text = &mat.as_str()[1..mat.end() - 1];
+            tokens.push(SpannedToken::new(Token::Str(text.to_string()), pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
+
+        } else if let Some(symbol) = operators.symbol_at(str_left) {
+            let end = pos + symbol.len();
+            tokens.push(SpannedToken::new(Token::Operator(symbol.to_string()), pos, end));
+
+            str_left = str_tail_at(str_left, symbol.len());
 
-            tokens.push(Token::Plus);
+        } else if let Some(mat) = REG_DOUBLE_EQUAL.find(str_left) {
+            tokens.push(SpannedToken::new(Token::DoubleEqual, pos, pos + mat.end()));
 
             str_left = str_tail_at(str_left, mat.end());
 
-        } else if let Some(mat) = REG_SUB.find(str_left) {
-            let mat_str = mat.as_str();
-            pos += mat_str.len();
+        } else if let Some(mat) = REG_EQUAL.find(str_left) {
+            tokens.push(SpannedToken::new(Token::Equal, pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
 
-            tokens.push(Token::Hyphen);
+        } else if let Some(mat) = REG_COMMA.find(str_left) {
+            tokens.push(SpannedToken::new(Token::Comma, pos, pos + mat.end()));
 
             str_left = str_tail_at(str_left, mat.end());
 
-        } else if let Some(mat) = REG_MULT.find(str_left) {
-            let mat_str = mat.as_str();
-            pos += mat_str.len();
+        } else if let Some(mat) = REG_PLUS.find(str_left) {
+            tokens.push(SpannedToken::new(Token::Plus, pos, pos + mat.end()));
 
-            tokens.push(Token::Asterisk);
+            str_left = str_tail_at(str_left, mat.end());
+
+        } else if let Some(mat) = REG_SUB.find(str_left) {
+            tokens.push(SpannedToken::new(Token::Hyphen, pos, pos + mat.end()));
 
             str_left = str_tail_at(str_left, mat.end());
 
-        } else if let Some(mat) = REG_DIV.find(str_left) {
-            let mat_str = mat.as_str();
-            pos += mat_str.len();
+        } else if let Some(mat) = REG_MULT.find(str_left) {
+            tokens.push(SpannedToken::new(Token::Asterisk, pos, pos + mat.end()));
 
-            tokens.push(Token::Slash);
+            str_left = str_tail_at(str_left, mat.end());
+
+        } else if let Some(mat) = REG_DIV.find(str_left) {
+            tokens.push(SpannedToken::new(Token::Slash, pos, pos + mat.end()));
 
             str_left = str_tail_at(str_left, mat.end());
 
         } else if let Some(mat) = REG_MOD.find(str_left) {
-            let mat_str = mat.as_str();
-            pos += mat_str.len();
-
-            tokens.push(Token::Percent);
+            tokens.push(SpannedToken::new(Token::Percent, pos, pos + mat.end()));
 
             str_left = str_tail_at(str_left, mat.end());
 
         } else if let Some(mat) = REG_HAT.find(str_left) {
-            let mat_str = mat.as_str();
-            pos += mat_str.len();
+            tokens.push(SpannedToken::new(Token::Hat, pos, pos + mat.end()));
 
-            tokens.push(Token::Hat);
+            str_left = str_tail_at(str_left, mat.end());
+
+        } else if let Some(mat) = REG_NOT_EQUAL.find(str_left) {
+            tokens.push(SpannedToken::new(Token::NotEqual, pos, pos + mat.end()));
 
             str_left = str_tail_at(str_left, mat.end());
 
-        } else if let Some(mat) = REG_OPEN_PAREN.find(str_left) {
-            let mat_str = mat.as_str();
-            pos += mat_str.len();
+        } else if let Some(mat) = REG_BANG.find(str_left) {
+            tokens.push(SpannedToken::new(Token::Bang, pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
 
-            tokens.push(Token::OpenParen);
+        } else if let Some(mat) = REG_DOUBLE_AMPERSAND.find(str_left) {
+            tokens.push(SpannedToken::new(Token::DoubleAmpersand, pos, pos + mat.end()));
 
             str_left = str_tail_at(str_left, mat.end());
 
-        } else if let Some(mat) = REG_CLOSE_PAREN.find(str_left) {
-            let mat_str = mat.as_str();
-            pos += mat_str.len();
+        } else if let Some(mat) = REG_DOUBLE_PIPE.find(str_left) {
+            tokens.push(SpannedToken::new(Token::DoublePipe, pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
 
-            tokens.push(Token::CloseParen);
+        } else if let Some(mat) = REG_AMPERSAND.find(str_left) {
+            tokens.push(SpannedToken::new(Token::Ampersand, pos, pos + mat.end()));
 
             str_left = str_tail_at(str_left, mat.end());
 
-        } else if let Some(mat) = REG_SPACE.find(str_left) {
-            let mat_str = mat.as_str();
-            pos += mat_str.len();
+        } else if let Some(mat) = REG_PIPE.find(str_left) {
+            tokens.push(SpannedToken::new(Token::Pipe, pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
+
+        } else if let Some(mat) = REG_TILDE.find(str_left) {
+            tokens.push(SpannedToken::new(Token::Tilde, pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
+
+        } else if let Some(mat) = REG_SHIFT_LEFT.find(str_left) {
+            tokens.push(SpannedToken::new(Token::ShiftLeft, pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
+
+        } else if let Some(mat) = REG_LOGICAL_SHIFT_RIGHT.find(str_left) {
+            tokens.push(SpannedToken::new(Token::LogicalShiftRight, pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
+
+        } else if let Some(mat) = REG_SHIFT_RIGHT.find(str_left) {
+            tokens.push(SpannedToken::new(Token::ShiftRight, pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
+
+        } else if let Some(mat) = REG_LESS_EQUAL.find(str_left) {
+            tokens.push(SpannedToken::new(Token::LessEqual, pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
+
+        } else if let Some(mat) = REG_LESS.find(str_left) {
+            tokens.push(SpannedToken::new(Token::Less, pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
+
+        } else if let Some(mat) = REG_GREATER_EQUAL.find(str_left) {
+            tokens.push(SpannedToken::new(Token::GreaterEqual, pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
+
+        } else if let Some(mat) = REG_GREATER.find(str_left) {
+            tokens.push(SpannedToken::new(Token::Greater, pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
+
+        } else if let Some(mat) = REG_QUESTION.find(str_left) {
+            tokens.push(SpannedToken::new(Token::Question, pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
+
+        } else if let Some(mat) = REG_COLON.find(str_left) {
+            tokens.push(SpannedToken::new(Token::Colon, pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
+
+        } else if let Some(mat) = REG_OPEN_PAREN.find(str_left) {
+            tokens.push(SpannedToken::new(Token::OpenParen, pos, pos + mat.end()));
 
             str_left = str_tail_at(str_left, mat.end());
 
+        } else if let Some(mat) = REG_CLOSE_PAREN.find(str_left) {
+            tokens.push(SpannedToken::new(Token::CloseParen, pos, pos + mat.end()));
+
+            str_left = str_tail_at(str_left, mat.end());
+
+        } else if let Some(mat) = REG_SPACE.find(str_left) {
+            str_left = str_tail_at(str_left, mat.end());
+
         } else {
             return Err(TokenError::new_invalid_char(pos, str));
         }
     }
 }
 
+/// Converts a literal such as `0xFF` or `0b1010_0101` found at `pos` of `source`.
+fn parse_radix(literal: &str, pos: usize, source: &str) -> TokenResult<BigInt> {
+    let radix = match &literal[1..2] {
+        "x" | "X" => 16,
+        "o" | "O" => 8,
+        _ => 2,
+    };
+
+    let mut digits = String::with_capacity(literal.len());
+    for (i, c) in literal.char_indices().skip(2) {
+        match c {
+            '_' => (),
+            c if c.is_digit(radix) => digits.push(c),
+            _ => return Err(TokenError::new_invalid_digit(pos + i, radix, source)),
+        }
+    }
+
+    // Digits are required after the prefix.
+    BigInt::parse_bytes(digits.as_bytes(), radix)
+        .ok_or_else(|| TokenError::new_invalid_digit(pos + literal.len(), radix, source))
+}
+
 fn str_tail_at(str: &str, at: usize) -> &str {
     if at < str.len() { &str[at..] } else { "" }
 }
@@ -216,6 +440,12 @@ fn str_tail_at(str: &str, at: usize) -> &str {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use expression::operator::Associativity;
+    use expression::operator::Notation;
+
+    fn kinds(tokens: Vec<SpannedToken>) -> Vec<Token> {
+        tokens.into_iter().map(|t| t.token).collect()
+    }
 
     //// parse_token ////
 
@@ -223,39 +453,179 @@ mod tests {
     fn parse_token_number_1() {
         let tokens = parse_token("1");
 
-        let tokens = tokens.expect("Test returns Err().");
-        assert_eq!(tokens, vec![Token::Number(1)]);
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        assert_eq!(tokens, vec![Token::Number(1.into())]);
     }
 
     #[test]
     fn parse_token_number_neg_1() {
         let tokens = parse_token("(-1)");
 
-        let tokens = tokens.expect("Test returns Err().");
-        assert_eq!(tokens, vec![Token::Number(-1)]);
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        assert_eq!(
+            tokens,
+            vec![Token::OpenParen, Token::Hyphen, Token::Number(1.into()), Token::CloseParen]
+        );
     }
 
     #[test]
     fn parse_token_number_max() {
         let tokens = parse_token("9223372036854775807");
 
-        let tokens = tokens.expect("Test returns Err().");
-        assert_eq!(tokens, vec![Token::Number(9223372036854775807)]);
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        assert_eq!(tokens, vec![Token::Number(9223372036854775807i64.into())]);
     }
 
     #[test]
     fn parse_token_number_min() {
-        let tokens = parse_token("(-9223372036854775808)");
+        let tokens = parse_token("-9223372036854775808");
 
-        let tokens = tokens.expect("Test returns Err().");
-        assert_eq!(tokens, vec![Token::Number(-9223372036854775808)]);
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        let expected = BigInt::from(9223372036854775807i64) + 1;
+        assert_eq!(tokens, vec![Token::Hyphen, Token::Number(expected)]);
+    }
+
+    #[test]
+    fn parse_token_number_over_max() {
+        let tokens = parse_token("9223372036854775808");
+
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        let expected = BigInt::from(9223372036854775807i64) + 1;
+        assert_eq!(tokens, vec![Token::Number(expected)]);
+    }
+
+    #[test]
+    fn parse_token_decimal() {
+        let tokens = parse_token("1.5");
+
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        assert_eq!(tokens, vec![Token::Decimal("1.5".to_string())]);
+    }
+
+    #[test]
+    fn parse_token_decimal_without_integer_part() {
+        let tokens = parse_token(".25");
+
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        assert_eq!(tokens, vec![Token::Decimal(".25".to_string())]);
+    }
+
+    #[test]
+    fn parse_token_scientific() {
+        let tokens = parse_token("6.02e23 * 1E-9");
+
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Decimal("6.02e23".to_string()),
+                Token::Asterisk,
+                Token::Decimal("1E-9".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_token_radix() {
+        let tokens = parse_token("0xFF + 0o755 - 0B1010_0101");
+
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Number(255.into()),
+                Token::Plus,
+                Token::Number(493.into()),
+                Token::Hyphen,
+                Token::Number(165.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_token_separators() {
+        let tokens = parse_token("1_000_000 + 1_000.5e1_0");
+
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Number(1000000.into()),
+                Token::Plus,
+                Token::Decimal("1000.5e10".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_token_invalid_digit() {
+        let result = parse_token("1 + 0b102");
+
+        let err = result.expect_err("This test should be return error.");
+        assert_eq!(err.span(), Span::new(8, 9));
+        assert_eq!(
+            err.to_string(),
+            "2 進数の数字ではない文字が含まれています。\n入力: \"1 + 0b102\"\n               ^ 不正な数字\n"
+        );
+    }
+
+    #[test]
+    fn parse_token_radix_without_digits() {
+        let result = parse_token("0x + 1");
+
+        let err = result.expect_err("This test should be return error.");
+        assert_eq!(err.span(), Span::new(2, 3));
+        match err {
+            TokenError::InvalidDigit { radix, .. } => assert_eq!(radix, 16),
+            e => panic!("Unexpected error in test:\n{:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_token_ident() {
+        let tokens = parse_token("x_1 foo");
+
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        assert_eq!(
+            tokens,
+            vec![Token::Ident("x_1".to_string()), Token::Ident("foo".to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_token_assign() {
+        let tokens = parse_token("x = 3");
+
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        assert_eq!(
+            tokens,
+            vec![Token::Ident("x".to_string()), Token::Equal, Token::Number(3.into())]
+        );
+    }
+
+    #[test]
+    fn parse_token_call() {
+        let tokens = parse_token("max(a, 2)");
+
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Ident("max".to_string()),
+                Token::OpenParen,
+                Token::Ident("a".to_string()),
+                Token::Comma,
+                Token::Number(2.into()),
+                Token::CloseParen,
+            ]
+        );
     }
 
     #[test]
     fn parse_token_add() {
         let tokens = parse_token("+");
 
-        let tokens = tokens.expect("Test returns Err().");
+        let tokens = kinds(tokens.expect("Test returns Err()."));
         assert_eq!(tokens, vec![Token::Plus]);
     }
 
@@ -263,7 +633,7 @@ mod tests {
     fn parse_token_sub() {
         let tokens = parse_token("-");
 
-        let tokens = tokens.expect("Test returns Err().");
+        let tokens = kinds(tokens.expect("Test returns Err()."));
         assert_eq!(tokens, vec![Token::Hyphen]);
     }
 
@@ -271,7 +641,7 @@ mod tests {
     fn parse_token_mult() {
         let tokens = parse_token("*");
 
-        let tokens = tokens.expect("Test returns Err().");
+        let tokens = kinds(tokens.expect("Test returns Err()."));
         assert_eq!(tokens, vec![Token::Asterisk]);
     }
 
@@ -279,7 +649,7 @@ mod tests {
     fn parse_token_div() {
         let tokens = parse_token("/");
 
-        let tokens = tokens.expect("Test returns Err().");
+        let tokens = kinds(tokens.expect("Test returns Err()."));
         assert_eq!(tokens, vec![Token::Slash]);
     }
 
@@ -287,15 +657,76 @@ mod tests {
     fn parse_token_mod() {
         let tokens = parse_token("%");
 
-        let tokens = tokens.expect("Test returns Err().");
+        let tokens = kinds(tokens.expect("Test returns Err()."));
         assert_eq!(tokens, vec![Token::Percent]);
     }
 
+    #[test]
+    fn parse_token_bitwise() {
+        let tokens = parse_token("a&b | ~c xor d<<1 >> 2>>>3");
+
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Ident("a".to_string()),
+                Token::Ampersand,
+                Token::Ident("b".to_string()),
+                Token::Pipe,
+                Token::Tilde,
+                Token::Ident("c".to_string()),
+                Token::Xor,
+                Token::Ident("d".to_string()),
+                Token::ShiftLeft,
+                Token::Number(1.into()),
+                Token::ShiftRight,
+                Token::Number(2.into()),
+                Token::LogicalShiftRight,
+                Token::Number(3.into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_token_comparison_and_logic() {
+        let tokens = parse_token("a==b != c<=d<e >=f>g && !h||i ? j : k");
+
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        let ident = |name: &str| Token::Ident(name.to_string());
+        assert_eq!(
+            tokens,
+            vec![
+                ident("a"),
+                Token::DoubleEqual,
+                ident("b"),
+                Token::NotEqual,
+                ident("c"),
+                Token::LessEqual,
+                ident("d"),
+                Token::Less,
+                ident("e"),
+                Token::GreaterEqual,
+                ident("f"),
+                Token::Greater,
+                ident("g"),
+                Token::DoubleAmpersand,
+                Token::Bang,
+                ident("h"),
+                Token::DoublePipe,
+                ident("i"),
+                Token::Question,
+                ident("j"),
+                Token::Colon,
+                ident("k"),
+            ]
+        );
+    }
+
     #[test]
     fn parse_token_open_paren() {
         let tokens = parse_token("(");
 
-        let tokens = tokens.expect("Test returns Err().");
+        let tokens = kinds(tokens.expect("Test returns Err()."));
         assert_eq!(tokens, vec![Token::OpenParen]);
     }
 
@@ -303,19 +734,19 @@ mod tests {
     fn parse_token_close_paren() {
         let tokens = parse_token(")");
 
-        let tokens = tokens.expect("Test returns Err().");
+        let tokens = kinds(tokens.expect("Test returns Err()."));
         assert_eq!(tokens, vec![Token::CloseParen]);
     }
 
     #[test]
     fn parse_token_invalid() {
-        let result = parse_token("?");
+        let result = parse_token("$");
 
         let err = result.expect_err("This test should be return error.");
 
         match err {
-            TokenError::InvalidChar(_) => (),
-            // e => panic!("Unexpected error in test:\n{:?}", e),
+            TokenError::InvalidChar { .. } => (),
+            e => panic!("Unexpected error in test:\n{:?}", e),
         }
     }
 
@@ -323,10 +754,10 @@ mod tests {
     fn parse_token_add_expr() {
         let tokens = parse_token("1+2");
 
-        let tokens = tokens.expect("Test returns Err().");
+        let tokens = kinds(tokens.expect("Test returns Err()."));
         assert_eq!(
             tokens,
-            vec![Token::Number(1), Token::Plus, Token::Number(2)]
+            vec![Token::Number(1.into()), Token::Plus, Token::Number(2.into())]
         );
     }
 
@@ -334,10 +765,10 @@ mod tests {
     fn parse_token_with_spaces() {
         let tokens = parse_token(" 1  + 2 ");
 
-        let tokens = tokens.expect("Test returns Err().");
+        let tokens = kinds(tokens.expect("Test returns Err()."));
         assert_eq!(
             tokens,
-            vec![Token::Number(1), Token::Plus, Token::Number(2)]
+            vec![Token::Number(1.into()), Token::Plus, Token::Number(2.into())]
         );
     }
 
@@ -345,17 +776,125 @@ mod tests {
     fn parse_token_complex_1() {
         let tokens = parse_token("(1 + 3) %(-6)");
 
-        let tokens = tokens.expect("Test returns Err().");
+        let tokens = kinds(tokens.expect("Test returns Err()."));
         assert_eq!(
             tokens,
             vec![
                 Token::OpenParen,
-                Token::Number(1),
+                Token::Number(1.into()),
                 Token::Plus,
-                Token::Number(3),
+                Token::Number(3.into()),
                 Token::CloseParen,
                 Token::Percent,
-                Token::Number(-6),
+                Token::OpenParen,
+                Token::Hyphen,
+                Token::Number(6.into()),
+                Token::CloseParen,
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_token_spans() {
+        let tokens = parse_token("  12 +(-3)");
+
+        let tokens = tokens.expect("Test returns Err().");
+        assert_eq!(
+            tokens,
+            vec![
+                SpannedToken::new(Token::Number(12.into()), 2, 4),
+                SpannedToken::new(Token::Plus, 5, 6),
+                SpannedToken::new(Token::OpenParen, 6, 7),
+                SpannedToken::new(Token::Hyphen, 7, 8),
+                SpannedToken::new(Token::Number(3.into()), 8, 9),
+                SpannedToken::new(Token::CloseParen, 9, 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_token_invalid_found() {
+        let result = parse_token("1 + ＃");
+
+        let err = result.expect_err("This test should be return error.");
+        assert_eq!(err.span(), Span::new(4, 7));
+        match err {
+            TokenError::InvalidChar { position, found, .. } => {
+                assert_eq!((position, found), (4, '＃'))
+            }
+            e => panic!("Unexpected error in test:\n{:?}", e),
+        }
+    }
+
+    #[test]
+    fn parse_token_invalid_position() {
+        let result = parse_token("1 +  $");
+
+        let err = result.expect_err("This test should be return error.");
+        assert_eq!(
+            err.to_string(),
+            "トークン化できない入力が検出されました。\n入力: \"1 +  $\"\n            ^ 不正な文字\n"
+        );
+    }
+
+    #[test]
+    fn parse_token_str() {
+        let tokens = parse_token("infix 5 \"<+>\"");
+
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Ident("infix".to_string()),
+                Token::Number(5.into()),
+                Token::Str("<+>".to_string()),
+            ]
+        );
+    }
+
+    //// parse_token_with ////
+
+    #[test]
+    fn parse_token_with_custom_operators() {
+        let mut operators = OperatorTable::new();
+        for symbol in &["<+>", "avg"] {
+            let notation = Notation::infix(symbol, 5, Associativity::Left);
+            operators.declare(notation.expect("Test returns Err()."));
+        }
+
+        let tokens = parse_token_with("1<+>-2 avg avgs", &operators);
+
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Number(1.into()),
+                Token::Operator("<+>".to_string()),
+                Token::Hyphen,
+                Token::Number(2.into()),
+                Token::Operator("avg".to_string()),
+                Token::Ident("avgs".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_token_with_longest_symbol() {
+        let mut operators = OperatorTable::new();
+        let notation = Notation::infix("<<<", 9, Associativity::Left);
+        operators.declare(notation.expect("Test returns Err()."));
+
+        let tokens = parse_token_with("1 <<< 2 << 3", &operators);
+
+        let tokens = kinds(tokens.expect("Test returns Err()."));
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Number(1.into()),
+                Token::Operator("<<<".to_string()),
+                Token::Number(2.into()),
+                Token::ShiftLeft,
+                Token::Number(3.into()),
             ]
         );
     }
@@ -0,0 +1,470 @@
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::One;
+use num_traits::Signed;
+use num_traits::Zero;
+
+use execute::IntType;
+use execute::Mode;
+use execute::value::Value;
+
+
+/// How results are printed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Format {
+    /// Exact fraction, e.g. `7/2`.
+    #[default]
+    Fraction,
+    /// Whole part and proper fraction, e.g. `3 1/2`.
+    Mixed,
+    /// Decimal approximation with the given number of fractional digits, e.g. `3.5`.
+    Decimal(usize),
+    /// Hexadecimal integer, e.g. `0xff`.
+    Hex,
+    /// Octal integer, e.g. `0o755`.
+    Oct,
+    /// Binary integer, e.g. `0b1010`.
+    Bin,
+    /// Digits grouped by thousands, e.g. `1,234,567`.
+    Grouped,
+    /// Scientific notation with the given number of significant digits, e.g. `1.23e4`.
+    Scientific(usize),
+    /// Like `Scientific` with the exponent a multiple of 3, e.g. `12.3e3`.
+    Engineering(usize),
+    /// Positional notation rounded to the given number of significant digits, e.g. `12300`.
+    Significant(usize),
+}
+
+/// Number of fractional digits of `Format::Decimal` when not specified.
+pub const DEFAULT_DECIMAL_DIGITS: usize = 10;
+
+/// Number of significant digits of the scientific formats when not specified.
+pub const DEFAULT_SIGNIFICANT_DIGITS: usize = 6;
+
+/// Upper bound of the digits given to `decimal` and the formats counting significant digits.
+pub const MAX_DIGITS: usize = 1000;
+
+impl Format {
+    /// Converts a format name such as `hex`.
+    /// `precision` is used by `decimal` and the formats counting significant digits.
+    /// Returns `None` when it exceeds `MAX_DIGITS`.
+    pub fn from_name(name: &str, precision: Option<usize>) -> Option<Format> {
+        if precision > Some(MAX_DIGITS) {
+            return None;
+        }
+        let significant = precision.unwrap_or(DEFAULT_SIGNIFICANT_DIGITS);
+        match name {
+            "fraction" => Some(Format::Fraction),
+            "mixed" => Some(Format::Mixed),
+            "decimal" => Some(Format::Decimal(precision.unwrap_or(DEFAULT_DECIMAL_DIGITS))),
+            "hex" => Some(Format::Hex),
+            "oct" => Some(Format::Oct),
+            "bin" => Some(Format::Bin),
+            "grouped" => Some(Format::Grouped),
+            "scientific" | "sci" if significant > 0 => Some(Format::Scientific(significant)),
+            "engineering" | "eng" if significant > 0 => Some(Format::Engineering(significant)),
+            "significant" | "sig" if significant > 0 => Some(Format::Significant(significant)),
+            _ => None,
+        }
+    }
+}
+
+/// Splits a trailing conversion such as `255 to hex` or `x to sci 3` from `line`.
+/// Returns the line unchanged when it does not end with a known format.
+pub fn split_conversion(line: &str) -> (&str, Option<Format>) {
+    let words: Vec<&str> = line.split_whitespace().collect();
+
+    let conversion = match *words.as_slice() {
+        [_, .., "to", name, digits] => match digits.parse::<usize>() {
+            Ok(digits) => Format::from_name(name, Some(digits)).map(|format| (format, 3)),
+            Err(_) => None,
+        },
+        _ => None,
+    };
+    let conversion = conversion.or_else(|| match *words.as_slice() {
+        [_, .., "to", name] => Format::from_name(name, None).map(|format| (format, 2)),
+        _ => None,
+    });
+
+    match conversion {
+        Some((format, count)) => {
+            let to = line.trim_end().len() - suffix_len(line, count);
+            (line[..to].trim_end(), Some(format))
+        }
+        None => (line, None),
+    }
+}
+
+/// Byte length of the last `count` words of `line`, including the whitespace between them.
+fn suffix_len(line: &str, count: usize) -> usize {
+    let line = line.trim_end();
+    let mut start = line.len();
+    for _ in 0..count {
+        let head = line[..start].trim_end();
+        start = head.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    }
+    line.len() - start
+}
+
+/// Renders `value` in `format`. Formats not applicable to the value fall back to its `Display`.
+pub fn format_value(value: &Value, format: Format) -> String {
+    match (value, format) {
+        (Value::Rational(r), Format::Mixed) => format_mixed(r),
+        (Value::Rational(r), Format::Decimal(digits)) => format_decimal(r, digits),
+        (Value::Float(f), Format::Decimal(digits)) => match BigRational::from_float(*f) {
+            Some(r) => format_decimal(&r, digits),
+            None => value.to_string(),
+        },
+        (value, Format::Hex) => format_radix(value, 16, "0x"),
+        (value, Format::Oct) => format_radix(value, 8, "0o"),
+        (value, Format::Bin) => format_radix(value, 2, "0b"),
+        (Value::Integer(n), Format::Grouped) => group(&n.to_string()),
+        (Value::Rational(r), Format::Grouped) => group(&r.to_string()),
+        (Value::Float(_), Format::Grouped) if !value.to_string().contains('e') => {
+            group(&value.to_string())
+        }
+        (value, Format::Scientific(digits)) => match exact(value) {
+            Some(r) => format_scientific(&r, digits, 1),
+            None => value.to_string(),
+        },
+        (value, Format::Engineering(digits)) => match exact(value) {
+            Some(r) => format_scientific(&r, digits, 3),
+            None => value.to_string(),
+        },
+        (value, Format::Significant(digits)) => match exact(value) {
+            Some(r) => format_significant(&r, digits),
+            None => value.to_string(),
+        },
+        (value, _) => value.to_string(),
+    }
+}
+
+/// Renders a result. In the programmer mode an integer is followed by its bit pattern,
+/// e.g. `-1  0xff  0b1111_1111` for `i8`.
+pub fn format_result(value: &Value, format: Format, mode: Mode) -> String {
+    match (value, mode) {
+        (Value::Integer(n), Mode::Programmer(int, _)) => {
+            format!("{}  {}", format_value(value, format), format_bits(n, int))
+        }
+        _ => format_value(value, format),
+    }
+}
+
+/// Two's complement bits of `n` as `int` in hex and binary, padded to the width of `int`.
+/// Digits are separated by `_` every four, as literals may be written.
+pub fn format_bits(n: &BigInt, int: IntType) -> String {
+    let bits = int.to_bits(n);
+    let width = int.bits() as usize;
+
+    let hex = format!("{:0>1$}", bits.to_str_radix(16), width / 4);
+    let bin = format!("{:0>1$}", bits.to_str_radix(2), width);
+    format!("0x{}  0b{}", separate(&hex), separate(&bin))
+}
+
+/// Inserts `_` every four digits from the right.
+// `usize::is_multiple_of` needs Rust 1.87.
+#[allow(clippy::manual_is_multiple_of)]
+fn separate(digits: &str) -> String {
+    let len = digits.len();
+    let mut separated = String::with_capacity(len + len / 4);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (len - i) % 4 == 0 {
+            separated.push('_');
+        }
+        separated.push(c);
+    }
+    separated
+}
+
+/// Exact value of `value`, or `None` for a float that is not finite and a boolean.
+fn exact(value: &Value) -> Option<BigRational> {
+    match *value {
+        Value::Integer(ref n) => Some(BigRational::from_integer(n.clone())),
+        Value::Rational(ref r) => Some(r.clone()),
+        Value::Float(f) => BigRational::from_float(f),
+        Value::Bool(_) => None,
+    }
+}
+
+/// Renders an integral value with `prefix`. Other values fall back to their `Display`.
+fn format_radix(value: &Value, radix: u32, prefix: &str) -> String {
+    match exact(value) {
+        Some(ref r) if r.is_integer() => {
+            let n = r.to_integer();
+            let sign = if n.is_negative() { "-" } else { "" };
+            format!("{}{}{}", sign, prefix, n.abs().to_str_radix(radix))
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Inserts `,` every three digits of the integer parts of the numbers in `s`.
+fn group(s: &str) -> String {
+    let mut grouped = String::with_capacity(s.len() + s.len() / 3);
+    let mut rest = s;
+
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        grouped.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let len = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let (digits, tail) = rest.split_at(len);
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (len - i) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(c);
+        }
+        rest = tail;
+
+        // Fractional digits are not grouped.
+        if rest.starts_with('.') {
+            let len = rest[1..].find(|c: char| !c.is_ascii_digit()).map_or(rest.len(), |i| i + 1);
+            grouped.push_str(&rest[..len]);
+            rest = &rest[len..];
+        }
+    }
+
+    grouped.push_str(rest);
+    grouped
+}
+
+/// Rounds `|r|` to `digits` significant digits.
+/// Returns the digits and the exponent of the first one, i.e. `d.ddd × 10^exponent`.
+fn significant_digits(r: &BigRational, digits: usize) -> (String, i64) {
+    let r = r.abs();
+    if r.is_zero() {
+        return ("0".repeat(digits), 0);
+    }
+
+    // Estimate from the lengths of the numerator and denominator, then correct by one.
+    let mut exponent =
+        r.numer().to_string().len() as i64 - r.denom().to_string().len() as i64;
+    if r < pow10(exponent) {
+        exponent -= 1;
+    }
+
+    let mut scaled = (&r / pow10(exponent - digits as i64 + 1)).round().to_integer();
+    if scaled.to_string().len() > digits {
+        // Rounded up to the next power of ten, e.g. 9.99 to 10.0.
+        exponent += 1;
+        scaled = scaled.div_floor(&BigInt::from(10));
+    }
+
+    (scaled.to_string(), exponent)
+}
+
+fn pow10(exponent: i64) -> BigRational {
+    let scale = BigInt::from(10).pow(exponent.unsigned_abs() as u32);
+    if exponent >= 0 {
+        BigRational::from_integer(scale)
+    } else {
+        BigRational::new(BigInt::one(), scale)
+    }
+}
+
+/// Renders `r` as `mantissa e exponent` with the exponent a multiple of `step`.
+fn format_scientific(r: &BigRational, digits: usize, step: i64) -> String {
+    let (digits, exponent) = significant_digits(r, digits);
+    let mantissa_exponent = exponent.mod_floor(&step);
+    let exponent = exponent - mantissa_exponent;
+
+    let sign = if r.is_negative() { "-" } else { "" };
+    format!("{}{}e{}", sign, place_point(&digits, mantissa_exponent), exponent)
+}
+
+fn format_significant(r: &BigRational, digits: usize) -> String {
+    let (digits, exponent) = significant_digits(r, digits);
+
+    let sign = if r.is_negative() { "-" } else { "" };
+    format!("{}{}", sign, place_point(&digits, exponent))
+}
+
+/// Places the decimal point in `digits` so that the first digit has the place `10^exponent`.
+fn place_point(digits: &str, exponent: i64) -> String {
+    let whole = exponent + 1;
+    if whole <= 0 {
+        format!("0.{}{}", "0".repeat(whole.unsigned_abs() as usize), digits)
+    } else if whole as usize >= digits.len() {
+        format!("{}{}", digits, "0".repeat(whole as usize - digits.len()))
+    } else {
+        let (whole, fract) = digits.split_at(whole as usize);
+        format!("{}.{}", whole, fract)
+    }
+}
+
+fn format_mixed(r: &BigRational) -> String {
+    let whole = r.trunc();
+    let fract = (r - &whole).abs();
+
+    if whole.is_zero() || fract.is_zero() {
+        r.to_string()
+    } else {
+        format!("{} {}", whole, fract)
+    }
+}
+
+fn format_decimal(r: &BigRational, digits: usize) -> String {
+    let scale = BigRational::from_integer(BigInt::from(10).pow(digits as u32));
+    let scaled = (r * scale).round().to_integer();
+
+    let mut s = scaled.abs().to_string();
+    if s.len() <= digits {
+        s = "0".repeat(digits + 1 - s.len()) + &s;
+    }
+
+    let (whole, fract) = s.split_at(s.len() - digits);
+    let fract = fract.trim_end_matches('0');
+
+    let sign = if scaled.is_negative() { "-" } else { "" };
+    if fract.is_empty() {
+        format!("{}{}", sign, whole)
+    } else {
+        format!("{}{}.{}", sign, whole, fract)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use execute::Overflow;
+
+    fn ratio(n: i64, d: i64) -> Value {
+        Value::Rational(BigRational::new(n.into(), d.into()))
+    }
+
+    //// format_value ////
+
+    #[test]
+    fn format_value_integer() {
+        let s = format_value(&Value::Integer(42.into()), Format::Mixed);
+        assert_eq!(s, "42");
+    }
+
+    #[test]
+    fn format_value_fraction() {
+        assert_eq!(format_value(&ratio(7, 2), Format::Fraction), "7/2");
+        assert_eq!(format_value(&ratio(-14, 4), Format::Fraction), "-7/2");
+        assert_eq!(format_value(&ratio(8, 2), Format::Fraction), "4");
+    }
+
+    #[test]
+    fn format_value_mixed() {
+        assert_eq!(format_value(&ratio(7, 2), Format::Mixed), "3 1/2");
+        assert_eq!(format_value(&ratio(-7, 2), Format::Mixed), "-3 1/2");
+        assert_eq!(format_value(&ratio(1, 2), Format::Mixed), "1/2");
+        assert_eq!(format_value(&ratio(4, 1), Format::Mixed), "4");
+    }
+
+    #[test]
+    fn format_value_decimal() {
+        assert_eq!(format_value(&ratio(7, 2), Format::Decimal(10)), "3.5");
+        assert_eq!(format_value(&ratio(1, 3), Format::Decimal(5)), "0.33333");
+        assert_eq!(format_value(&ratio(-2, 3), Format::Decimal(3)), "-0.667");
+        assert_eq!(format_value(&ratio(1, 8), Format::Decimal(0)), "0");
+        assert_eq!(format_value(&ratio(-1, 1000), Format::Decimal(2)), "0");
+    }
+
+    #[test]
+    fn format_value_float_decimal() {
+        assert_eq!(format_value(&Value::Float(0.1 + 0.2), Format::Decimal(10)), "0.3");
+        assert_eq!(format_value(&Value::Float(-2.5), Format::Decimal(3)), "-2.5");
+        assert_eq!(
+            format_value(&Value::Float(0.1 + 0.2), Format::Fraction),
+            "0.30000000000000004"
+        );
+    }
+
+    #[test]
+    fn format_value_radix() {
+        assert_eq!(format_value(&Value::Integer(255.into()), Format::Hex), "0xff");
+        assert_eq!(format_value(&Value::Integer((-8).into()), Format::Oct), "-0o10");
+        assert_eq!(format_value(&Value::Integer(10.into()), Format::Bin), "0b1010");
+        assert_eq!(format_value(&ratio(8, 2), Format::Hex), "0x4");
+        assert_eq!(format_value(&ratio(7, 2), Format::Hex), "7/2");
+        assert_eq!(format_value(&Value::Float(16.0), Format::Hex), "0x10");
+    }
+
+    #[test]
+    fn format_value_grouped() {
+        assert_eq!(format_value(&Value::Integer(1234567.into()), Format::Grouped), "1,234,567");
+        assert_eq!(format_value(&Value::Integer((-123).into()), Format::Grouped), "-123");
+        assert_eq!(format_value(&ratio(-10001, 1000), Format::Grouped), "-10,001/1,000");
+        assert_eq!(format_value(&Value::Float(12345.6789), Format::Grouped), "12,345.6789");
+    }
+
+    #[test]
+    fn format_value_scientific() {
+        assert_eq!(format_value(&Value::Integer(12345.into()), Format::Scientific(3)), "1.23e4");
+        assert_eq!(format_value(&ratio(-1, 3), Format::Scientific(2)), "-3.3e-1");
+        assert_eq!(format_value(&Value::Float(9.999), Format::Scientific(3)), "1.00e1");
+        assert_eq!(format_value(&Value::Integer(7.into()), Format::Scientific(1)), "7e0");
+    }
+
+    #[test]
+    fn format_value_engineering() {
+        assert_eq!(format_value(&Value::Integer(12345.into()), Format::Engineering(3)), "12.3e3");
+        assert_eq!(format_value(&Value::Float(0.00047), Format::Engineering(2)), "470e-6");
+        assert_eq!(format_value(&Value::Integer(123456.into()), Format::Engineering(2)), "120e3");
+    }
+
+    #[test]
+    fn format_value_significant() {
+        assert_eq!(format_value(&ratio(22, 7), Format::Significant(4)), "3.143");
+        assert_eq!(format_value(&Value::Integer(12345.into()), Format::Significant(2)), "12000");
+        assert_eq!(format_value(&Value::Float(0.000123456), Format::Significant(3)), "0.000123");
+        assert_eq!(format_value(&Value::Integer(0.into()), Format::Significant(3)), "0.00");
+    }
+
+    //// format_result ////
+
+    #[test]
+    fn format_result_programmer_mode() {
+        let mode = Mode::Programmer(IntType::I16, Overflow::Checked);
+
+        assert_eq!(
+            format_result(&Value::Integer((-2).into()), Format::Fraction, mode),
+            "-2  0xfffe  0b1111_1111_1111_1110"
+        );
+        assert_eq!(format_result(&Value::Integer(5.into()), Format::Fraction, Mode::Int64), "5");
+    }
+
+    #[test]
+    fn format_bits_padded() {
+        assert_eq!(format_bits(&5.into(), IntType::U8), "0x05  0b0000_0101");
+        assert_eq!(
+            format_bits(&(-1).into(), IntType::U32),
+            "0xffff_ffff  0b1111_1111_1111_1111_1111_1111_1111_1111"
+        );
+    }
+
+    //// Format::from_name ////
+
+    #[test]
+    fn from_name() {
+        assert_eq!(Format::from_name("hex", None), Some(Format::Hex));
+        assert_eq!(Format::from_name("decimal", None), Some(Format::Decimal(10)));
+        assert_eq!(Format::from_name("sci", Some(3)), Some(Format::Scientific(3)));
+        assert_eq!(Format::from_name("significant", None), Some(Format::Significant(6)));
+        assert_eq!(Format::from_name("sig", Some(0)), None);
+        assert_eq!(Format::from_name("decimal", Some(MAX_DIGITS + 1)), None);
+        assert_eq!(Format::from_name("roman", None), None);
+    }
+
+    //// split_conversion ////
+
+    #[test]
+    fn split_conversion_name() {
+        assert_eq!(split_conversion("255 to hex"), ("255", Some(Format::Hex)));
+        assert_eq!(split_conversion("x*2  to  sci 3 "), ("x*2", Some(Format::Scientific(3))));
+    }
+
+    #[test]
+    fn split_conversion_none() {
+        assert_eq!(split_conversion("255"), ("255", None));
+        assert_eq!(split_conversion("to hex"), ("to hex", None));
+        assert_eq!(split_conversion("x to y"), ("x to y", None));
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt::Write;
+
+use rcalc::CalcError;
+use rcalc::Format;
+use rcalc::Lang;
+use rcalc::Value;
+use rcalc::format;
+
+
+/// Object for a line evaluated successfully. A definition has no value.
+pub fn result(input: &str, value: Option<&Value>, format: Format) -> String {
+    match value {
+        Some(value) => format!(
+            "{{\"input\":{},\"value\":{},\"type\":{}}}",
+            string(input),
+            string(&format::format_value(value, format)),
+            string(value.type_name())
+        ),
+        None => format!("{{\"input\":{},\"value\":null,\"type\":\"definition\"}}", string(input)),
+    }
+}
+
+/// Object for an error. The message leaves the position to `span`, which is `null` for errors
+/// not tied to a position in the input. `input` is `null` for errors reading an input file.
+pub fn error(input: Option<&str>, err: &CalcError, lang: Lang) -> String {
+    let span = match err.span() {
+        Some(span) => format!("[{},{}]", span.start, span.end),
+        None => "null".to_string(),
+    };
+
+    format!(
+        "{{\"input\":{},\"error\":{{\"kind\":{},\"message\":{},\"span\":{}}}}}",
+        input.map_or("null".to_string(), string),
+        string(err.code()),
+        string(&err.message().text(lang)),
+        span
+    )
+}
+
+/// Quotes `s` as a JSON string.
+fn string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rcalc::Calculator;
+    use std::io;
+
+    fn eval_error(line: &str) -> CalcError {
+        Calculator::new().eval(line).expect_err("Test should returns Err().")
+    }
+
+    //// result ////
+
+    #[test]
+    fn result_value() {
+        let value = Value::Integer(3.into());
+
+        assert_eq!(
+            result("1 + 2", Some(&value), Format::Fraction),
+            r#"{"input":"1 + 2","value":"3","type":"integer"}"#
+        );
+    }
+
+    #[test]
+    fn result_definition() {
+        assert_eq!(
+            result("f(x) = x", None, Format::Fraction),
+            r#"{"input":"f(x) = x","value":null,"type":"definition"}"#
+        );
+    }
+
+    //// error ////
+
+    #[test]
+    fn error_with_span() {
+        let json = error(Some("1 + $"), &eval_error("1 + $"), Lang::En);
+
+        assert_eq!(
+            json,
+            r#"{"input":"1 + $","error":{"kind":"invalid_char","#.to_string()
+                + r#""message":"Found input that cannot be tokenized.","span":[4,5]}}"#
+        );
+    }
+
+    #[test]
+    fn error_without_span() {
+        let json = error(Some("1 / 0"), &eval_error("1 / 0"), Lang::En);
+
+        assert_eq!(
+            json,
+            r#"{"input":"1 / 0","error":{"kind":"division_by_zero","#.to_string()
+                + r#""message":"Division by zero: 1 / 0","span":null}}"#
+        );
+    }
+
+    #[test]
+    fn error_without_input() {
+        let e = CalcError::Io(io::Error::new(io::ErrorKind::NotFound, "not found"));
+        let json = error(None, &e, Lang::En);
+
+        assert_eq!(
+            json,
+            r#"{"input":null,"error":{"kind":"io","message":"I/O error: not found","span":null}}"#
+        );
+    }
+
+    //// string ////
+
+    #[test]
+    fn string_escapes() {
+        assert_eq!(string("a\"b\\c\u{1}"), r#""a\"b\\c\u0001""#);
+    }
+}
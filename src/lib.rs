@@ -0,0 +1,60 @@
+//! Calculator for integer, rational and floating-point expressions.
+//!
+//! ```
+//! let expr = rcalc::parse("1 + 2 * 3").unwrap();
+//! let value = rcalc::evaluate(expr).unwrap();
+//! assert_eq!(value.to_string(), "7");
+//! ```
+//!
+//! Use `Calculator` to keep variables, functions and settings between evaluations.
+
+#![allow(clippy::four_forward_slashes)]
+
+extern crate num_bigint;
+extern crate num_integer;
+extern crate num_rational;
+extern crate num_traits;
+extern crate regex;
+#[macro_use]
+extern crate lazy_static;
+#[cfg(test)]
+extern crate proptest;
+
+pub mod error;
+pub mod expression;
+pub mod execute;
+pub mod format;
+pub mod message;
+mod calculator;
+
+pub use calculator::Calculator;
+pub use calculator::Entry;
+pub use calculator::LAST_RESULT_NAME;
+pub use error::CalcError;
+pub use error::Result;
+pub use execute::IntType;
+pub use execute::Mode;
+pub use execute::Overflow;
+pub use execute::environment::Environment;
+pub use execute::environment::OperatorFunction;
+pub use execute::value::Value;
+pub use expression::Expression;
+pub use expression::Statement;
+pub use expression::operator::Associativity;
+pub use expression::operator::Fixity;
+pub use expression::operator::Notation;
+pub use expression::printer::Style;
+pub use format::Format;
+pub use message::Lang;
+
+
+/// Parses an expression such as `2 * (3 + x)`.
+/// A trailing conversion such as `to hex` is accepted and dropped, as it only selects a format.
+pub fn parse(source: &str) -> Result<Expression> {
+    expression::parse_expr(format::split_conversion(source).0)
+}
+
+/// Evaluates an expression in the default mode, without variables.
+pub fn evaluate(expr: Expression) -> Result<Value> {
+    Ok(execute::execute(expr, Mode::default(), &mut Environment::new())?)
+}
@@ -1,53 +1,205 @@
-extern crate regex;
-#[macro_use]
-extern crate lazy_static;
+#![allow(clippy::four_forward_slashes)]
 
-mod error;
-mod expression;
-mod execute;
+extern crate rcalc;
 
+mod batch;
+mod cli;
+mod json;
+
+use std::env;
 use std::io;
 use std::io::Write;
+use std::process;
+
+use rcalc::CalcError;
+use rcalc::Calculator;
+use rcalc::Format;
+use rcalc::IntType;
+use rcalc::Lang;
+use rcalc::Mode;
+use rcalc::Overflow;
+use rcalc::Result;
+use rcalc::execute::environment::MAX_DEPTH;
+use rcalc::format;
+use rcalc::format::MAX_DIGITS;
+use rcalc::message::Message;
+use rcalc::message::MessageKind;
 
-use error::CalcError;
-use error::Result;
+use cli::Command;
 
 fn main() {
-    match calc_expr() {
-        Ok(()) => (),
-        Err(CalcError::Token(err)) => {
-            println!("{}", err.get_message());
+    let options = match cli::parse_args(env::args().skip(1)) {
+        Ok(Command::Run(options)) => options,
+        Ok(Command::Help) => {
+            print!("{}", MessageKind::Usage.template(Lang::from_env()));
+            return;
         }
-        Err(CalcError::Parse(err)) => {
-            println!("{}", err.get_message());
+        Ok(Command::Version) => {
+            println!("rcalc {}", env!("CARGO_PKG_VERSION"));
+            return;
         }
-        Err(CalcError::Execute(err)) => {
-            println!("{}", err.get_message());
+        Err(message) => {
+            let lang = Lang::from_env();
+            eprintln!("{}", message.render(lang));
+            eprintln!("{}", MessageKind::SeeHelp.template(lang));
+            process::exit(cli::EXIT_USAGE);
+        }
+    };
+    let lang = options.lang.unwrap_or_else(Lang::from_env);
+
+    let mut calc = Calculator::new();
+    calc.set_mode(options.mode);
+    calc.set_format(options.format);
+
+    if !options.inputs.is_empty() {
+        let report = batch::Report { lang, output: options.output };
+        process::exit(batch::run_all(&mut calc, &options.inputs, report));
+    }
+
+    let prompt = if options.quiet { "" } else { MessageKind::Prompt.template(lang) };
+    // Exit code of the first failed line, so that piped input can be checked.
+    let mut status = 0;
+    loop {
+        match repl_step(&mut calc, prompt, lang) {
+            Ok(true) => continue,
+            Ok(false) => break,
+            Err(err @ CalcError::Io(_)) => {
+                eprintln!("{}", err.localize(lang));
+                status = err.exit_code();
+                break;
+            }
+            Err(err) => {
+                eprintln!("{}", err.localize(lang));
+                if status == 0 {
+                    status = err.exit_code();
+                }
+            }
         }
-        Err(err) => println!("Internal error: {}", err),
     }
+    process::exit(status);
 }
 
-fn calc_expr() -> Result<()> {
-    print!("数式:");
+/// Reads and evaluates one line. Returns `Ok(false)` when the REPL should end.
+fn repl_step(calc: &mut Calculator, prompt: &str, lang: Lang) -> Result<bool> {
+    print!("{}", prompt);
     io::stdout().flush()?;
 
-    let mut line = read_line()?;
-    line.pop();
+    let line = match read_line()? {
+        Some(line) => line,
+        None => {
+            if !prompt.is_empty() {
+                println!();
+            }
+            return Ok(false);
+        }
+    };
+    let line = line.trim();
 
-    let expr = expression::parse_expr(&line)?;
-    let result = execute::execute(expr)?;
+    if line.is_empty() {
+        return Ok(true);
+    }
 
-    println!("= {}", result);
+    if line.starts_with(':') {
+        return Ok(run_command(calc, line, lang));
+    }
 
-    Ok(())
+    if let Some(result) = calc.eval(line)? {
+        println!("= {}", format::format_result(&result, calc.last_format(), calc.mode()));
+    }
+
+    Ok(true)
 }
 
-fn read_line() -> Result<String> {
+fn run_command(calc: &mut Calculator, command: &str, lang: Lang) -> bool {
+    let mut words = command.split_whitespace();
+
+    match (words.next().unwrap_or(""), words.next()) {
+        (":quit", None) | (":q", None) => return false,
+        (":mode", None) => println!("{:?}", calc.mode()),
+        (":mode", Some("int64")) => calc.set_mode(Mode::Int64),
+        (":mode", Some("bigint")) => calc.set_mode(Mode::BigInt),
+        (":mode", Some("rational")) => calc.set_mode(Mode::Rational),
+        (":mode", Some("float")) => calc.set_mode(Mode::Float),
+        (":mode", Some(name)) => {
+            let overflow = match words.next() {
+                None | Some("checked") => Overflow::Checked,
+                Some("wrapping") => Overflow::Wrapping,
+                Some(_) => {
+                    report(MessageKind::UnknownCommand, command, lang);
+                    return true;
+                }
+            };
+            match IntType::from_name(name) {
+                Some(int) => calc.set_mode(Mode::Programmer(int, overflow)),
+                None => report(MessageKind::UnknownIntType, command, lang),
+            }
+        }
+        (":format", None) => println!("{:?}", calc.format()),
+        (":format", Some(name)) => {
+            let precision = match words.next().map(|digits| digits.parse::<usize>()) {
+                None => None,
+                Some(Ok(digits)) if digits <= MAX_DIGITS => Some(digits),
+                Some(_) => {
+                    report(MessageKind::InvalidDigits, command, lang);
+                    return true;
+                }
+            };
+            match Format::from_name(name, precision) {
+                Some(format) => calc.set_format(format),
+                None => report(MessageKind::UnknownFormat, command, lang),
+            }
+        }
+        (":depth", None) => println!("{}", calc.max_depth()),
+        (":depth", Some(depth)) => match depth.parse::<usize>() {
+            Ok(depth) if depth <= MAX_DEPTH => calc.set_max_depth(depth),
+            _ => report(MessageKind::InvalidDepth, command, lang),
+        },
+        (":last", None) => {
+            if let Some(result) = calc.last_result() {
+                println!("= {}", format::format_result(result, calc.last_format(), calc.mode()));
+            }
+        }
+        (":vars", None) => {
+            for (name, value) in calc.environment().variables() {
+                println!("{} = {}", name, format::format_value(value, calc.format()));
+            }
+        }
+        (":funcs", None) => {
+            for (name, function) in calc.environment().functions() {
+                println!("{}({})", name, function.params.join(", "));
+            }
+        }
+        (":ops", None) => {
+            for notation in calc.environment().operators().custom() {
+                println!("{}", notation);
+            }
+        }
+        (":history", None) => {
+            for (i, entry) in calc.history().iter().enumerate() {
+                let format = entry.conversion.unwrap_or(calc.format());
+                let result = format::format_value(&entry.result, format);
+                println!("{:>4}: {} = {}", i + 1, entry.input, result);
+            }
+        }
+        _ => report(MessageKind::UnknownCommand, command, lang),
+    }
+
+    true
+}
+
+/// Writes a diagnostic about `command` to stderr.
+fn report(kind: MessageKind, command: &str, lang: Lang) {
+    eprintln!("{}", Message::new(kind).arg(command).render(lang));
+}
+
+/// Reads one line from stdin. Returns `None` at EOF.
+fn read_line() -> Result<Option<String>> {
     let stdin = io::stdin();
 
     let mut line = String::new();
-    stdin.read_line(&mut line)?;
+    if stdin.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
 
-    Ok(line)
+    Ok(Some(line))
 }
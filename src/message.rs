@@ -0,0 +1,522 @@
+use std::env;
+use std::fmt;
+use std::fmt::Display;
+
+use expression::span::Span;
+
+
+/// Language of messages shown to the user.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Lang {
+    #[default]
+    Ja,
+    En,
+}
+
+impl Lang {
+    /// Every supported language.
+    pub const ALL: &'static [Lang] = &[Lang::Ja, Lang::En];
+
+    pub fn from_name(name: &str) -> Option<Lang> {
+        match name {
+            "ja" => Some(Lang::Ja),
+            "en" => Some(Lang::En),
+            _ => None,
+        }
+    }
+
+    /// Language selected by the `LANG` environment variable.
+    /// Japanese locales select `Ja`, other locales `En`, and an unset variable the default.
+    pub fn from_env() -> Lang {
+        match env::var("LANG") {
+            Ok(ref locale) if locale.starts_with("ja") => Lang::Ja,
+            Ok(ref locale) if !locale.is_empty() => Lang::En,
+            _ => Lang::default(),
+        }
+    }
+}
+
+/// Entry of the message catalog.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MessageKind {
+    // Tokenizer and parser errors, and labels of their annotations.
+    InvalidChar,
+    InvalidCharLabel,
+    InvalidDigit,
+    InvalidDigitLabel,
+    MismatchParen,
+    MismatchParenLabel,
+    NoToken,
+    NoTokenLabel,
+    TooMuchToken,
+    TooMuchTokenLabel,
+    UnexpectedToken,
+    UnexpectedTokenLabel,
+    TooDeep,
+    TooDeepLabel,
+    /// Caption of the annotated source line.
+    Input,
+
+    // Execution errors.
+    DivisionByZero,
+    NegativeExponent,
+    FractionalExponent,
+    NotANumber,
+    Overflow,
+    OutOfRange,
+    OutOfDomain,
+    NotInteger,
+    TypeMismatch,
+    UndefinedVariable,
+    UndefinedFunction,
+    ArgumentCount,
+    ArgumentCountAtLeast,
+    RecursionLimit,
+    NameTaken,
+    Io,
+
+    // Command line and REPL.
+    Prompt,
+    Usage,
+    SeeHelp,
+    UnknownOption,
+    MissingValue,
+    UnknownLang,
+    UnknownOutput,
+    UnknownFormat,
+    UnknownIntType,
+    InvalidDigits,
+    InvalidDepth,
+    UnknownCommand,
+}
+
+impl MessageKind {
+    /// Every entry of the catalog.
+    pub const ALL: &'static [MessageKind] = &[
+        MessageKind::InvalidChar,
+        MessageKind::InvalidCharLabel,
+        MessageKind::InvalidDigit,
+        MessageKind::InvalidDigitLabel,
+        MessageKind::MismatchParen,
+        MessageKind::MismatchParenLabel,
+        MessageKind::NoToken,
+        MessageKind::NoTokenLabel,
+        MessageKind::TooMuchToken,
+        MessageKind::TooMuchTokenLabel,
+        MessageKind::UnexpectedToken,
+        MessageKind::UnexpectedTokenLabel,
+        MessageKind::TooDeep,
+        MessageKind::TooDeepLabel,
+        MessageKind::Input,
+        MessageKind::DivisionByZero,
+        MessageKind::NegativeExponent,
+        MessageKind::FractionalExponent,
+        MessageKind::NotANumber,
+        MessageKind::Overflow,
+        MessageKind::OutOfRange,
+        MessageKind::OutOfDomain,
+        MessageKind::NotInteger,
+        MessageKind::TypeMismatch,
+        MessageKind::UndefinedVariable,
+        MessageKind::UndefinedFunction,
+        MessageKind::ArgumentCount,
+        MessageKind::ArgumentCountAtLeast,
+        MessageKind::RecursionLimit,
+        MessageKind::NameTaken,
+        MessageKind::Io,
+        MessageKind::Prompt,
+        MessageKind::Usage,
+        MessageKind::SeeHelp,
+        MessageKind::UnknownOption,
+        MessageKind::MissingValue,
+        MessageKind::UnknownLang,
+        MessageKind::UnknownOutput,
+        MessageKind::UnknownFormat,
+        MessageKind::UnknownIntType,
+        MessageKind::InvalidDigits,
+        MessageKind::InvalidDepth,
+        MessageKind::UnknownCommand,
+    ];
+
+    /// Text of this entry in `lang`. `{0}`, `{1}`, ... are replaced with arguments.
+    pub fn template(self, lang: Lang) -> &'static str {
+        match lang {
+            Lang::Ja => self.ja(),
+            Lang::En => self.en(),
+        }
+    }
+
+    fn ja(self) -> &'static str {
+        match self {
+            MessageKind::InvalidChar => "トークン化できない入力が検出されました。",
+            MessageKind::InvalidCharLabel => "不正な文字",
+            MessageKind::InvalidDigit => "{0} 進数の数字ではない文字が含まれています。",
+            MessageKind::InvalidDigitLabel => "不正な数字",
+            MessageKind::MismatchParen => "括弧の対応が取れていません。",
+            MessageKind::MismatchParenLabel => "対応する括弧がありません",
+            MessageKind::NoToken => "式の途中でトークンが無くなりました。",
+            MessageKind::NoTokenLabel => "被演算子がありません",
+            MessageKind::TooMuchToken => "トークンが多すぎます。",
+            MessageKind::TooMuchTokenLabel => "余分なトークン",
+            MessageKind::UnexpectedToken => "ここでは使えないトークンです。",
+            MessageKind::UnexpectedTokenLabel => "不正なトークン",
+            MessageKind::TooDeep => "括弧や演算子の入れ子が深すぎます。",
+            MessageKind::TooDeepLabel => "ここで上限を超えました",
+            MessageKind::Input => "入力",
+            MessageKind::DivisionByZero => "ゼロ除算エラー: {0} / 0",
+            MessageKind::NegativeExponent => "指数が負です",
+            MessageKind::FractionalExponent => "指数が整数ではありません",
+            MessageKind::NotANumber => "結果が数値ではありません: {0}",
+            MessageKind::Overflow => "オーバーフローしました: {0}",
+            MessageKind::OutOfRange => "値が範囲外です: {0}",
+            MessageKind::OutOfDomain => "定義域外の引数です: {0}",
+            MessageKind::NotInteger => "引数が整数ではありません: {0}",
+            MessageKind::TypeMismatch => "型が合いません: {2} 型の値 {1} に {0} は使えません",
+            MessageKind::UndefinedVariable => "未定義の変数です: {0}",
+            MessageKind::UndefinedFunction => "未定義の関数です: {0}",
+            MessageKind::ArgumentCount => {
+                "引数の数が正しくありません: {0}() の引数は {1} 個ですが、{2} 個が渡されました"
+            }
+            MessageKind::ArgumentCountAtLeast => {
+                "引数の数が正しくありません: {0}() の引数は {1} 個以上ですが、{2} 個が渡されました"
+            }
+            MessageKind::RecursionLimit => "関数呼び出しが深すぎます: {0}() (上限 {1} 段)",
+            MessageKind::NameTaken => "関数名か変数名を演算子にはできません: {0}",
+            MessageKind::Io => "入出力エラー: {0}",
+            MessageKind::Prompt => "数式:",
+            MessageKind::Usage => USAGE_JA,
+            MessageKind::SeeHelp => "詳しくは rcalc --help を参照してください",
+            MessageKind::UnknownOption => "不明なオプションです: {0}",
+            MessageKind::MissingValue => "オプションに値が必要です: {0}",
+            MessageKind::UnknownLang => "不明な言語です: {0}",
+            MessageKind::UnknownOutput => "不明な出力スタイルです: {0}",
+            MessageKind::UnknownFormat => "不明な出力形式です: {0}",
+            MessageKind::UnknownIntType => "不明な整数型です: {0}",
+            MessageKind::InvalidDigits => "桁数が不正です: {0}",
+            MessageKind::InvalidDepth => "深さが不正です: {0}",
+            MessageKind::UnknownCommand => "不明なコマンドです: {0}",
+        }
+    }
+
+    fn en(self) -> &'static str {
+        match self {
+            MessageKind::InvalidChar => "Found input that cannot be tokenized.",
+            MessageKind::InvalidCharLabel => "invalid character",
+            MessageKind::InvalidDigit => "Found a character that is not a base-{0} digit.",
+            MessageKind::InvalidDigitLabel => "invalid digit",
+            MessageKind::MismatchParen => "Parentheses are not balanced.",
+            MessageKind::MismatchParenLabel => "no matching parenthesis",
+            MessageKind::NoToken => "The expression ended unexpectedly.",
+            MessageKind::NoTokenLabel => "missing operand",
+            MessageKind::TooMuchToken => "Too many tokens.",
+            MessageKind::TooMuchTokenLabel => "extra token",
+            MessageKind::UnexpectedToken => "This token cannot be used here.",
+            MessageKind::UnexpectedTokenLabel => "invalid token",
+            MessageKind::TooDeep => "Parentheses or operators are nested too deeply.",
+            MessageKind::TooDeepLabel => "limit exceeded here",
+            MessageKind::Input => "input",
+            MessageKind::DivisionByZero => "Division by zero: {0} / 0",
+            MessageKind::NegativeExponent => "Negative exponent",
+            MessageKind::FractionalExponent => "Exponent is not an integer",
+            MessageKind::NotANumber => "Result is not a number: {0}",
+            MessageKind::Overflow => "Overflow: {0}",
+            MessageKind::OutOfRange => "Value out of range: {0}",
+            MessageKind::OutOfDomain => "Argument out of domain: {0}",
+            MessageKind::NotInteger => "Argument is not an integer: {0}",
+            MessageKind::TypeMismatch => "Type mismatch: {0} cannot be applied to {1} of type {2}",
+            MessageKind::UndefinedVariable => "Undefined variable: {0}",
+            MessageKind::UndefinedFunction => "Undefined function: {0}",
+            MessageKind::ArgumentCount => {
+                "Wrong number of arguments: {0}() takes {1} argument(s) but {2} were given"
+            }
+            MessageKind::ArgumentCountAtLeast => {
+                "Wrong number of arguments: {0}() takes at least {1} argument(s) but {2} were given"
+            }
+            MessageKind::RecursionLimit => "Function calls nested too deeply: {0}() (limit {1})",
+            MessageKind::NameTaken => "A function or variable name cannot be an operator: {0}",
+            MessageKind::Io => "I/O error: {0}",
+            MessageKind::Prompt => "expr:",
+            MessageKind::Usage => USAGE_EN,
+            MessageKind::SeeHelp => "See rcalc --help for details.",
+            MessageKind::UnknownOption => "Unknown option: {0}",
+            MessageKind::MissingValue => "Option requires a value: {0}",
+            MessageKind::UnknownLang => "Unknown language: {0}",
+            MessageKind::UnknownOutput => "Unknown output style: {0}",
+            MessageKind::UnknownFormat => "Unknown format: {0}",
+            MessageKind::UnknownIntType => "Unknown integer type: {0}",
+            MessageKind::InvalidDigits => "Invalid number of digits: {0}",
+            MessageKind::InvalidDepth => "Invalid depth: {0}",
+            MessageKind::UnknownCommand => "Unknown command: {0}",
+        }
+    }
+}
+
+/// Catalog entry with its arguments, rendered in any language on demand.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+    kind: MessageKind,
+    args: Vec<String>,
+    annotation: Option<Annotation>,
+}
+
+/// Source line with a caret under the span, rendered after the message.
+#[derive(Clone, Debug, PartialEq)]
+struct Annotation {
+    span: Span,
+    source: String,
+    label: MessageKind,
+}
+
+impl Message {
+    pub fn new(kind: MessageKind) -> Message {
+        Message {
+            kind,
+            args: Vec::new(),
+            annotation: None,
+        }
+    }
+
+    /// Appends an argument, substituted for the next placeholder.
+    pub fn arg<T: Display>(mut self, arg: T) -> Message {
+        self.args.push(arg.to_string());
+        self
+    }
+
+    /// Points at `span` of `source` with `label`.
+    pub fn annotate(mut self, span: Span, source: &str, label: MessageKind) -> Message {
+        self.annotation = Some(Annotation {
+            span,
+            source: source.to_string(),
+            label,
+        });
+        self
+    }
+
+    pub fn kind(&self) -> MessageKind {
+        self.kind
+    }
+
+    /// Renders the message alone, without the annotated source line.
+    pub fn text(&self, lang: Lang) -> String {
+        substitute(self.kind.template(lang), &self.args)
+    }
+
+    pub fn render(&self, lang: Lang) -> String {
+        let mut s = self.text(lang);
+
+        if let Some(ref annotation) = self.annotation {
+            s += "\n";
+            s += &annotation.span.annotate(
+                &annotation.source,
+                MessageKind::Input.template(lang),
+                annotation.label.template(lang),
+            );
+        }
+
+        s
+    }
+}
+
+impl fmt::Display for Message {
+    /// Renders in the default language.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(Lang::default()))
+    }
+}
+
+/// Replaces `{0}`, `{1}`, ... in `template` with `args`.
+fn substitute(template: &str, args: &[String]) -> String {
+    let mut s = template.to_string();
+    for (i, arg) in args.iter().enumerate() {
+        s = s.replace(&format!("{{{}}}", i), arg);
+    }
+    s
+}
+
+const USAGE_JA: &str = "\
+使い方: rcalc [オプション] [数式...]
+
+数式もファイルも指定しない場合は対話モードで起動します。
+
+オプション:
+  -e, --expr <数式>        数式を評価する (複数指定可)
+  -f, --file <ファイル>    ファイルの各行を評価する (- は標準入力)
+      --batch              標準入力の各行を評価する
+  -q, --quiet              プロンプトを表示しない
+      --format <形式>      出力形式: fraction, mixed, decimal, hex, oct, bin, grouped,
+                           scientific, engineering, significant
+      --precision <桁数>   decimal 形式の小数点以下の桁数、または有効数字の桁数
+      --lang <言語>        表示言語: ja, en (省略時は環境変数 LANG に従う)
+      --output <形式>      数式やファイルの評価結果の出力スタイル: text, json
+      --bigint             多倍長整数で計算する
+      --rational           有理数で計算する
+      --float              浮動小数点数で計算する
+      --int <型>           固定幅の整数で計算し、結果を 16 進数と 2 進数でも表示する:
+                           i8, u8, i16, u16, i32, u32, i64, u64
+      --wrapping           --int の型 (省略時は i64) の範囲を超えた結果を切り詰める
+  -h, --help               このヘルプを表示する
+  -V, --version            バージョンを表示する
+
+式の末尾に `to hex` のように形式名を付けると、その結果だけを指定の形式で表示します。
+
+終了コード:
+  0  成功
+  2  コマンドライン引数が不正
+  3  トークン化できない入力
+  4  数式の構文エラー
+  5  計算エラー (ゼロ除算、オーバーフローなど)
+  6  入出力エラー
+
+複数の行が失敗した場合は、最初に失敗した行の終了コードを返します。
+";
+
+const USAGE_EN: &str = "\
+Usage: rcalc [OPTIONS] [EXPRESSION...]
+
+Starts the interactive mode when neither expressions nor files are given.
+
+Options:
+  -e, --expr <EXPR>        Evaluate an expression (may be repeated)
+  -f, --file <FILE>        Evaluate each line of a file (- for stdin)
+      --batch              Evaluate each line of stdin
+  -q, --quiet              Do not show the prompt
+      --format <FORMAT>    Output format: fraction, mixed, decimal, hex, oct, bin, grouped,
+                           scientific, engineering, significant
+      --precision <N>      Fractional digits of decimal, or significant digits
+      --lang <LANG>        Language: ja, en (defaults to the LANG environment variable)
+      --output <STYLE>     Style of results of expressions and files: text, json
+      --bigint             Use arbitrary-precision integers
+      --rational           Use exact fractions
+      --float              Use floating-point numbers
+      --int <TYPE>         Use fixed-width integers and also show results in hex and binary:
+                           i8, u8, i16, u16, i32, u32, i64, u64
+      --wrapping           Wrap results out of the range of --int (i64 if omitted)
+  -h, --help               Show this help
+  -V, --version            Show the version
+
+Appending a format name such as `to hex` to an expression shows only that result in the format.
+
+Exit codes:
+  0  Success
+  2  Invalid command-line arguments
+  3  Input that cannot be tokenized
+  4  Syntax error in an expression
+  5  Evaluation error (division by zero, overflow, ...)
+  6  I/O error
+
+When several lines fail, the exit code of the first failure is returned.
+";
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn placeholders(template: &str) -> Vec<usize> {
+        (0..10).filter(|i| template.contains(&format!("{{{}}}", i))).collect()
+    }
+
+    //// MessageKind ////
+
+    #[test]
+    fn message_kind_every_language() {
+        for &kind in MessageKind::ALL {
+            let expected = placeholders(kind.template(Lang::default()));
+
+            for &lang in Lang::ALL {
+                let template = kind.template(lang);
+                assert!(!template.is_empty(), "{:?} is empty in {:?}", kind, lang);
+                assert_eq!(
+                    placeholders(template),
+                    expected,
+                    "{:?} has different arguments in {:?}",
+                    kind,
+                    lang
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn message_kind_all_is_complete() {
+        // Fails to compile when a kind is added without listing it here and in `ALL`.
+        fn index(kind: MessageKind) -> usize {
+            match kind {
+                MessageKind::InvalidChar => 0,
+                MessageKind::InvalidCharLabel => 1,
+                MessageKind::InvalidDigit => 2,
+                MessageKind::InvalidDigitLabel => 3,
+                MessageKind::MismatchParen => 4,
+                MessageKind::MismatchParenLabel => 5,
+                MessageKind::NoToken => 6,
+                MessageKind::NoTokenLabel => 7,
+                MessageKind::TooMuchToken => 8,
+                MessageKind::TooMuchTokenLabel => 9,
+                MessageKind::UnexpectedToken => 10,
+                MessageKind::UnexpectedTokenLabel => 11,
+                MessageKind::TooDeep => 12,
+                MessageKind::TooDeepLabel => 13,
+                MessageKind::Input => 14,
+                MessageKind::DivisionByZero => 15,
+                MessageKind::NegativeExponent => 16,
+                MessageKind::FractionalExponent => 17,
+                MessageKind::NotANumber => 18,
+                MessageKind::Overflow => 19,
+                MessageKind::OutOfRange => 20,
+                MessageKind::OutOfDomain => 21,
+                MessageKind::NotInteger => 22,
+                MessageKind::TypeMismatch => 23,
+                MessageKind::UndefinedVariable => 24,
+                MessageKind::UndefinedFunction => 25,
+                MessageKind::ArgumentCount => 26,
+                MessageKind::ArgumentCountAtLeast => 27,
+                MessageKind::RecursionLimit => 28,
+                MessageKind::NameTaken => 29,
+                MessageKind::Io => 30,
+                MessageKind::Prompt => 31,
+                MessageKind::Usage => 32,
+                MessageKind::SeeHelp => 33,
+                MessageKind::UnknownOption => 34,
+                MessageKind::MissingValue => 35,
+                MessageKind::UnknownLang => 36,
+                MessageKind::UnknownOutput => 37,
+                MessageKind::UnknownFormat => 38,
+                MessageKind::UnknownIntType => 39,
+                MessageKind::InvalidDigits => 40,
+                MessageKind::InvalidDepth => 41,
+                MessageKind::UnknownCommand => 42,
+            }
+        }
+
+        let indices: Vec<usize> = MessageKind::ALL.iter().map(|&kind| index(kind)).collect();
+        assert_eq!(indices, (0..43).collect::<Vec<_>>());
+    }
+
+    //// Message ////
+
+    #[test]
+    fn message_render_args() {
+        let message = Message::new(MessageKind::RecursionLimit).arg("f").arg(256);
+
+        assert_eq!(message.render(Lang::Ja), "関数呼び出しが深すぎます: f() (上限 256 段)");
+        assert_eq!(message.render(Lang::En), "Function calls nested too deeply: f() (limit 256)");
+    }
+
+    #[test]
+    fn message_render_annotation() {
+        let message = Message::new(MessageKind::NoToken).annotate(
+            Span::new(3, 3),
+            "1 +",
+            MessageKind::NoTokenLabel,
+        );
+
+        assert_eq!(
+            message.render(Lang::Ja),
+            "式の途中でトークンが無くなりました。\n入力: \"1 +\"\n          ^ 被演算子がありません\n"
+        );
+        assert_eq!(
+            message.render(Lang::En),
+            "The expression ended unexpectedly.\ninput: \"1 +\"\n           ^ missing operand\n"
+        );
+    }
+}
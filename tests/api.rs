@@ -0,0 +1,144 @@
+#![allow(clippy::four_forward_slashes)]
+
+extern crate rcalc;
+
+use rcalc::CalcError;
+use rcalc::Calculator;
+use rcalc::Expression;
+use rcalc::Format;
+use rcalc::Lang;
+use rcalc::Mode;
+use rcalc::execute::error::ExecuteError;
+use rcalc::expression::span::Span;
+use rcalc::expression::error::ParseError;
+use rcalc::format::format_value;
+
+
+//// parse ////
+
+#[test]
+fn parse_expression() {
+    let result = rcalc::parse("1 + x");
+
+    let expr = result.expect("Test returns Err().");
+    assert_eq!(
+        expr,
+        Expression::Add(
+            Box::new(Expression::Number(1.into())),
+            Box::new(Expression::Variable("x".to_string())),
+        )
+    );
+}
+
+#[test]
+fn parse_rejects_assignment() {
+    let result = rcalc::parse("x = 1");
+
+    match result.expect_err("Test should returns Err().") {
+        CalcError::Parse(ParseError::UnexpectedToken { .. }) => (),
+        e => panic!("Unexcepted error: {:?}", e),
+    }
+}
+
+#[test]
+fn parse_error_data() {
+    let err = match rcalc::parse("(1 + 2") {
+        Err(CalcError::Parse(err)) => err,
+        result => panic!("Unexpected result: {:?}", result),
+    };
+
+    assert_eq!(err.code(), "mismatch_paren");
+    assert_eq!(err.span(), Span::new(0, 1));
+    assert_eq!(err.found(), "(");
+}
+
+//// evaluate ////
+
+#[test]
+fn evaluate_expression() {
+    let expr = rcalc::parse("2 ^ 10 - max(3, 4)").expect("Test returns Err().");
+
+    let result = rcalc::evaluate(expr);
+
+    assert_eq!(result.expect("Test returns Err().").to_string(), "1020");
+}
+
+#[test]
+fn evaluate_conversion() {
+    let expr = rcalc::parse("255 to hex").expect("Test returns Err().");
+
+    let result = rcalc::evaluate(expr);
+
+    assert_eq!(result.expect("Test returns Err().").to_string(), "255");
+}
+
+#[test]
+fn evaluate_undefined_variable() {
+    let expr = rcalc::parse("x + 1").expect("Test returns Err().");
+
+    match rcalc::evaluate(expr).expect_err("Test should returns Err().") {
+        CalcError::Execute(ExecuteError::UndefinedVariable(_)) => (),
+        e => panic!("Unexcepted error: {:?}", e),
+    }
+}
+
+//// Calculator ////
+
+#[test]
+fn calculator_keeps_environment() {
+    let mut calc = Calculator::new();
+
+    calc.eval("rate = 3").expect("Test returns Err().");
+    calc.eval("f(x) = x * rate").expect("Test returns Err().");
+    let result = calc.eval("f(5) + ans").expect("Test returns Err().");
+
+    assert_eq!(result.map(|value| value.to_string()), Some("18".to_string()));
+    assert_eq!(calc.history().len(), 2);
+    assert!(calc.environment().function("f").is_some());
+}
+
+#[test]
+fn calculator_settings() {
+    let mut calc = Calculator::new();
+    calc.set_mode(Mode::Rational);
+    calc.set_format(Format::Mixed);
+
+    let result = calc.eval("7 / 2").expect("Test returns Err().");
+
+    let value = result.expect("Test returns None.");
+    assert_eq!(format_value(&value, calc.format()), "3 1/2");
+}
+
+#[test]
+fn calculator_conversion() {
+    let mut calc = Calculator::new();
+
+    let result = calc.eval("255 to hex").expect("Test returns Err().");
+
+    let value = result.expect("Test returns None.");
+    assert_eq!(format_value(&value, calc.last_format()), "0xff");
+    assert_eq!(calc.format(), Format::Fraction);
+    assert_eq!(calc.history()[0].conversion, Some(Format::Hex));
+}
+
+#[test]
+fn calculator_error_message() {
+    let mut calc = Calculator::new();
+
+    let err = calc.eval("1 / 0").expect_err("Test should returns Err().");
+
+    assert_eq!(err.to_string(), "ゼロ除算エラー: 1 / 0");
+    assert_eq!(err.localize(Lang::En), "Division by zero: 1 / 0");
+}
+
+#[test]
+fn calculator_error_exit_code() {
+    let mut calc = Calculator::new();
+
+    let codes: Vec<i32> = ["1 $", "1 +", "1 / 0"]
+        .iter()
+        .map(|line| calc.eval(line).expect_err("Test should returns Err().").exit_code())
+        .collect();
+
+    assert_eq!(codes, vec![3, 4, 5]);
+}
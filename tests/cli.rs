@@ -0,0 +1,104 @@
+#![allow(clippy::four_forward_slashes)]
+
+use std::io::Write;
+use std::process::Command;
+use std::process::Output;
+use std::process::Stdio;
+
+
+fn rcalc(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rcalc"))
+        .args(args)
+        .env("LANG", "ja_JP.UTF-8")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to start rcalc.");
+
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().expect("Failed to wait rcalc.")
+}
+
+//// exit code ////
+
+#[test]
+fn exit_code_success() {
+    let output = rcalc(&["-e", "1 + 2"], "");
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "3\n");
+}
+
+#[test]
+fn exit_code_execute_error() {
+    let output = rcalc(&["-e", "1 / 0", "-e", "2"], "");
+
+    assert_eq!(output.status.code(), Some(5));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "2\n");
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "ゼロ除算エラー: 1 / 0\n");
+}
+
+#[test]
+fn exit_code_usage_error() {
+    let output = rcalc(&["--unknown"], "");
+
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn exit_code_repl_first_failure() {
+    let output = rcalc(&["--quiet"], "1 +\n1 / 0\n3\n");
+
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "= 3\n");
+}
+
+//// language ////
+
+#[test]
+fn lang_option() {
+    let output = rcalc(&["--lang", "en", "-e", "1 / 0"], "");
+
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "Division by zero: 1 / 0\n");
+}
+
+#[test]
+fn lang_prompt() {
+    let output = rcalc(&["--lang", "en"], "1 + 2\n");
+
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "expr:= 3\nexpr:\n");
+}
+
+//// output ////
+
+#[test]
+fn output_json() {
+    let output = rcalc(&["--output", "json", "--rational", "-e", "1 / 2", "-e", "(1"], "");
+
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).lines().collect::<Vec<_>>(),
+        vec![
+            r#"{"input":"1 / 2","value":"1/2","type":"rational"}"#,
+            concat!(
+                r#"{"input":"(1","error":{"kind":"mismatch_paren","#,
+                r#""message":"括弧の対応が取れていません。","span":[0,1]}}"#,
+            ),
+        ]
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+}
+
+//// programmer mode ////
+
+#[test]
+fn programmer_mode_bits() {
+    let output = rcalc(&["--int", "u8", "--wrapping", "-e", "0xf0 | 0x0f", "-e", "255 + 2"], "");
+
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "255  0xff  0b1111_1111\n1  0x01  0b0000_0001\n"
+    );
+}